@@ -1,30 +1,33 @@
-use std::collections::HashMap;
-use std::fs;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::Path;
 
 use eframe::egui;
+use serde::{Deserialize, Serialize};
 
 use crate::config;
-use crate::theme::detect_colors_in_content;
-use crate::theme::scanner::{scan_theme_files, scan_themes_dir};
-use crate::utils::color::DetectedColor;
-
-fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
-    fs::create_dir_all(dst)?;
-    for entry in fs::read_dir(src)? {
-        let entry = entry?;
-        let ty = entry.file_type()?;
-        let dest_path = dst.join(entry.file_name());
-        if ty.is_dir() {
-            copy_dir_all(&entry.path(), &dest_path)?;
-        } else {
-            fs::copy(entry.path(), dest_path)?;
-        }
-    }
-    Ok(())
-}
-
-#[derive(Clone, Copy, PartialEq, Debug, Default)]
+use crate::state::{self, AppState};
+use crate::theme::adjust::{transform_file_cache, ColorAdjustment};
+use crate::theme::apply::{spawn_apply_command, ApplyJob, ApplyOutput};
+use crate::theme::archive::{self, ArchiveInfo};
+use crate::theme::base16::Base16Scheme;
+use crate::theme::compare::{compare_themes, ThemeComparison};
+use crate::theme::export::{export_palette, PaletteFormat};
+use crate::theme::git::{self, GitJob, GitJobKind};
+use crate::theme::merge::MergePlan;
+use crate::theme::ops;
+use crate::theme::roots::{scan_roots, theme_protection, Protection, ThemeRoot};
+use crate::theme::scanner::{get_extension, load_theme_cache, read_theme_files};
+use crate::theme::search::{search_theme, ColorQuery, ThemeSearchResult};
+use crate::theme::semantic::{remap_by_role, RemapMode, ThemeColors};
+use crate::theme::similarity::{rank_similar, PaletteSignature};
+use crate::theme::thumbnail::load_thumbnail;
+use crate::theme::variant::LightVariant;
+use crate::theme::wal::WalColors;
+use crate::theme::{detect_colors_in_content, scan_theme_files};
+use crate::utils::color::{color_to_format, detect_color_format, ColorFormat, DetectedColor};
+use crate::utils::cvd::CvdMode;
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
 pub enum SortMode {
     Name,
     Color,
@@ -47,88 +50,175 @@ pub struct ColorEditTarget {
     pub original_format: ColorFormat,
 }
 
-#[derive(Clone, Debug, PartialEq)]
-pub enum ColorFormat {
-    Hex3,
-    Hex6,
-    Hex8,
-    Rgb,
-    Rgba,
+/// In-memory state restored by Undo.
+#[derive(Clone, Debug)]
+pub struct EditSnapshot {
+    pub file_cache: HashMap<String, String>,
+    pub modified_colors: HashMap<String, String>,
 }
 
-pub fn detect_color_format(text: &str) -> ColorFormat {
-    let text = text.trim();
-    if text.starts_with("rgba") {
-        ColorFormat::Rgba
-    } else if text.starts_with("rgb") {
-        ColorFormat::Rgb
-    } else if text.starts_with('#') {
-        match text.len() - 1 {
-            3 => ColorFormat::Hex3,
-            8 => ColorFormat::Hex8,
-            _ => ColorFormat::Hex6,
-        }
-    } else {
-        ColorFormat::Hex6
-    }
+const MAX_UNDO: usize = 50;
+
+/// Modal actions started from the themes panel.
+#[derive(Clone, Debug)]
+pub enum ThemeDialog {
+    Rename {
+        theme: String,
+        new_name: String,
+    },
+    Duplicate {
+        theme: String,
+        new_name: String,
+    },
+    Delete {
+        theme: String,
+    },
+    /// `root` is the writable theme folder the copy is created in.
+    SaveAs {
+        theme: String,
+        new_name: String,
+        root: String,
+    },
+    ExportPalette {
+        theme: String,
+        format: PaletteFormat,
+        path: String,
+    },
+    ExportArchive {
+        theme: String,
+        path: String,
+    },
+    /// `root` is the writable theme folder the clone is created in.
+    ImportGit {
+        url: String,
+        new_name: String,
+        root: String,
+    },
+    /// `info` is the checked archive content, refreshed when `path` changes.
+    ImportArchive {
+        path: String,
+        new_name: String,
+        root: String,
+        info: Option<Result<ArchiveInfo, String>>,
+    },
+    /// Recolor the selected theme from a pywal/wallust `colors.json`.
+    /// `colors` is the parsed file at `path`, `None` until read.
+    ImportWal {
+        path: String,
+        mode: RemapMode,
+        colors: Option<Result<WalColors, String>>,
+    },
+    /// Import a base16/base24 scheme as a new theme (`remap: None`) or onto
+    /// the selected theme. `scheme` is the parsed file at `path`, `None`
+    /// until read.
+    ImportScheme {
+        path: String,
+        new_name: String,
+        root: String,
+        remap: Option<RemapMode>,
+        scheme: Option<Result<Base16Scheme, String>>,
+    },
+    /// `tags` is the comma separated text being edited.
+    EditTags {
+        theme: String,
+        tags: String,
+    },
 }
 
-pub fn color_to_format(color: egui::Color32, format: &ColorFormat) -> String {
-    match format {
-        ColorFormat::Hex3 => {
-            let r = (color.r() / 17).to_string();
-            let g = (color.g() / 17).to_string();
-            let b = (color.b() / 17).to_string();
-            format!("#{}{}{}", r, g, b)
-        }
-        ColorFormat::Hex6 => {
-            format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
-        }
-        ColorFormat::Hex8 => {
-            format!(
-                "#{:02x}{:02x}{:02x}{:02x}",
-                color.r(),
-                color.g(),
-                color.b(),
-                color.a()
-            )
-        }
-        ColorFormat::Rgb => {
-            format!("rgb({}, {}, {})", color.r(), color.g(), color.b())
-        }
-        ColorFormat::Rgba => {
-            format!(
-                "rgba({}, {}, {}, {})",
-                color.r(),
-                color.g(),
-                color.b(),
-                color.a() as f32 / 255.0
-            )
-        }
-    }
+/// Commits of one theme shown in the history window.
+#[derive(Clone, Debug)]
+pub struct HistoryView {
+    pub theme: String,
+    /// `origin` of a theme installed from git, whose commits are upstream's
+    pub origin: Option<String>,
+    pub commits: Vec<git::CommitInfo>,
+    pub selected: Option<usize>,
+    pub diff: String,
+}
+
+/// State of the "Find color" window searching every theme.
+#[derive(Clone, Debug, Default)]
+pub struct ColorSearch {
+    pub query: String,
+    /// `None` until the first search is run
+    pub results: Option<Vec<ThemeSearchResult>>,
+}
+
+/// Themes ranked by palette distance to `theme`.
+#[derive(Clone, Debug)]
+pub struct SimilarThemes {
+    pub theme: String,
+    /// Theme name, signature and distance, closest first
+    pub ranked: Vec<(String, PaletteSignature, f32)>,
+}
+
+/// Palette strip and wallpaper texture drawn next to a theme name.
+pub struct LoadedThumbnail {
+    pub palette: Vec<egui::Color32>,
+    pub wallpaper: Option<egui::TextureHandle>,
 }
 
 pub struct OmarchyApp {
     pub themes_path: String,
     pub themes_path_backup: String,
+    pub extra_theme_paths: Vec<String>,
+    pub extra_theme_paths_backup: Vec<String>,
     pub save_prefix: String,
     pub save_prefix_backup: String,
+    pub apply_command: String,
+    pub apply_command_backup: String,
+    pub apply_dry_run: bool,
+    pub apply_dry_run_backup: bool,
+    pub wal_colors_path: String,
+    pub wal_colors_path_backup: String,
+    pub history_enabled: bool,
+    pub history_enabled_backup: bool,
+    pub protected_themes: Vec<String>,
+    pub protected_themes_backup: Vec<String>,
+    pub protected_roots: Vec<String>,
+    pub protected_roots_backup: Vec<String>,
+    pub package_paths: Vec<String>,
+    pub package_paths_backup: Vec<String>,
     pub show_settings: bool,
+    pub show_preview: bool,
+    pub show_apca: bool,
+    pub cvd_mode: CvdMode,
     pub theme_names: Vec<String>,
+    /// `themes_path` and the extra paths as scanned, in priority order
+    pub theme_roots: Vec<ThemeRoot>,
+    /// Index into `theme_roots` of each theme
+    pub theme_root_index: HashMap<String, usize>,
+    /// Themes that are only saved as a copy, with the reason
+    pub theme_protection: HashMap<String, Protection>,
     pub selected_theme_index: Option<usize>,
     pub theme_files: Vec<String>,
     pub selected_file_index: Option<usize>,
     pub error_message: Option<String>,
+    pub status_message: Option<String>,
     pub theme_sort_mode: SortMode,
     pub file_sort_mode: SortMode,
+    pub theme_filter: String,
+    /// Only list themes with this tag
+    pub tag_filter: Option<String>,
+    pub file_filter: String,
+    pub color_filter: String,
     pub theme_last_opened: HashMap<String, u64>,
+    pub favorites: BTreeSet<String>,
+    pub pin_favorites: bool,
+    pub theme_tags: BTreeMap<String, Vec<String>>,
+    /// Palette signatures of saved themes, filled on demand
+    pub theme_signatures: HashMap<String, PaletteSignature>,
+    /// Themes panel thumbnails, loaded as rows become visible
+    pub theme_thumbnails: HashMap<String, LoadedThumbnail>,
     pub file_last_opened: HashMap<String, u64>,
     pub open_counter: u64,
+    /// Last known inner window size, saved with the app state
+    pub window_size: Option<[f32; 2]>,
     pub enabled_extensions: HashMap<String, ExtensionConfig>,
     pub file_content: String,
     pub file_cache: HashMap<String, String>,
+    pub non_editable_files: HashSet<String>,
     pub detected_colors: Vec<DetectedColor>,
-    pub selected_color_id: Option<String>,
     pub config_source: Option<String>,
 
     // Color editing
@@ -136,11 +226,34 @@ pub struct OmarchyApp {
     pub picker_color: Option<egui::Color32>,
     pub modified_colors: HashMap<String, String>, // color_id -> new hex value
     pub has_unsaved_changes: bool,
+    pub undo_stack: Vec<EditSnapshot>,
+
+    // Theme-wide adjustments
+    pub show_adjust: bool,
+    pub adjustment: ColorAdjustment,
+
+    pub theme_dialog: Option<ThemeDialog>,
+    /// Open merge window, if any
+    pub merge: Option<MergePlan>,
+    /// Open comparison window, if any
+    pub compare: Option<ThemeComparison>,
+    /// Open history window, if any
+    pub history: Option<HistoryView>,
+    /// Open color search window, if any
+    pub color_search: Option<ColorSearch>,
+    /// Open similar themes window, if any
+    pub similar: Option<SimilarThemes>,
+    pub apply_output: Option<ApplyOutput>,
+    /// Apply command still running, if any
+    pub apply_job: Option<ApplyJob>,
+    /// Clone or pull still running, if any
+    pub git_job: Option<GitJob>,
 }
 
 impl OmarchyApp {
     pub fn new() -> Self {
         let (config, config_source) = config::load_config();
+        let state = state::load_state();
 
         let mut enabled_extensions = HashMap::new();
         for (ext, setting) in config.extensions {
@@ -154,66 +267,274 @@ impl OmarchyApp {
             );
         }
 
-        Self {
+        let mut app = Self {
             themes_path: config.general.themes_path.clone(),
             themes_path_backup: config.general.themes_path.clone(),
+            extra_theme_paths: config.general.extra_theme_paths.clone(),
+            extra_theme_paths_backup: config.general.extra_theme_paths.clone(),
             save_prefix: config.general.save_prefix.clone(),
             save_prefix_backup: config.general.save_prefix.clone(),
+            apply_command: config.apply.command.clone(),
+            apply_command_backup: config.apply.command.clone(),
+            apply_dry_run: config.apply.dry_run,
+            apply_dry_run_backup: config.apply.dry_run,
+            wal_colors_path: config.import.wal_colors.clone(),
+            wal_colors_path_backup: config.import.wal_colors.clone(),
+            history_enabled: config.history.enabled,
+            history_enabled_backup: config.history.enabled,
+            protected_themes: config.protection.themes.clone(),
+            protected_themes_backup: config.protection.themes.clone(),
+            protected_roots: config.protection.roots.clone(),
+            protected_roots_backup: config.protection.roots.clone(),
+            package_paths: config.protection.package_paths.clone(),
+            package_paths_backup: config.protection.package_paths.clone(),
             show_settings: false,
+            show_preview: true,
+            show_apca: false,
+            cvd_mode: CvdMode::Normal,
             theme_names: vec![],
+            theme_roots: vec![],
+            theme_root_index: HashMap::new(),
+            theme_protection: HashMap::new(),
             selected_theme_index: None,
             theme_files: vec![],
             selected_file_index: None,
             error_message: None,
-            theme_sort_mode: SortMode::LastOpened,
-            file_sort_mode: SortMode::LastOpened,
-            theme_last_opened: HashMap::new(),
-            file_last_opened: HashMap::new(),
-            open_counter: 0,
+            status_message: None,
+            theme_sort_mode: state.theme_sort_mode,
+            file_sort_mode: state.file_sort_mode,
+            theme_filter: String::new(),
+            tag_filter: None,
+            file_filter: String::new(),
+            color_filter: String::new(),
+            theme_last_opened: state.theme_last_opened,
+            favorites: state.favorites,
+            pin_favorites: state.pin_favorites,
+            theme_tags: state.theme_tags,
+            theme_signatures: HashMap::new(),
+            theme_thumbnails: HashMap::new(),
+            file_last_opened: state.file_last_opened,
+            open_counter: state.open_counter,
+            window_size: state.window_size,
             enabled_extensions,
             file_content: String::new(),
             file_cache: HashMap::new(),
+            non_editable_files: HashSet::new(),
             detected_colors: vec![],
-            selected_color_id: None,
             config_source,
             color_edit_target: None,
             picker_color: None,
             modified_colors: HashMap::new(),
             has_unsaved_changes: false,
+            undo_stack: vec![],
+            show_adjust: false,
+            adjustment: ColorAdjustment::default(),
+            theme_dialog: None,
+            merge: None,
+            compare: None,
+            history: None,
+            color_search: None,
+            similar: None,
+            apply_output: None,
+            apply_job: None,
+            git_job: None,
+        };
+        app.restore_selection(state.last_theme.as_deref(), state.last_file.as_deref());
+        app
+    }
+
+    /// Reopens the theme and file that were selected when the app last closed.
+    fn restore_selection(&mut self, theme: Option<&str>, file: Option<&str>) {
+        let Some(theme) = theme else {
+            return;
+        };
+        self.load_themes();
+        if !self.theme_names.iter().any(|n| n == theme) {
+            return;
+        }
+        match file {
+            Some(file) => self.open_theme_file(theme, file),
+            None => self.select_theme(theme),
         }
     }
 
+    /// Writes sort modes, open history, window size and selection to the
+    /// state file.
+    pub fn save_state(&self) {
+        let state = AppState {
+            theme_sort_mode: self.theme_sort_mode,
+            pin_favorites: self.pin_favorites,
+            file_sort_mode: self.file_sort_mode,
+            open_counter: self.open_counter,
+            theme_last_opened: self.theme_last_opened.clone(),
+            file_last_opened: self.file_last_opened.clone(),
+            favorites: self.favorites.clone(),
+            theme_tags: self.theme_tags.clone(),
+            last_theme: self.selected_theme_name(),
+            last_file: self
+                .selected_file_index
+                .and_then(|i| self.theme_files.get(i))
+                .cloned(),
+            window_size: self.window_size,
+        };
+        if let Err(e) = state::save_state(&state) {
+            eprintln!("[DEBUG] Failed to save state: {}", e);
+        }
+    }
+
+    /// `themes_path` followed by the extra theme paths that are set.
+    pub fn root_paths(&self) -> Vec<String> {
+        std::iter::once(&self.themes_path)
+            .chain(&self.extra_theme_paths)
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .collect()
+    }
+
+    /// The root folder `theme` was found in.
+    pub fn theme_root(&self, theme: &str) -> Option<&ThemeRoot> {
+        self.theme_root_index
+            .get(theme)
+            .and_then(|&i| self.theme_roots.get(i))
+    }
+
+    /// Root folder path of `theme`. New themes not scanned yet resolve to
+    /// `themes_path`.
+    pub fn theme_root_path(&self, theme: &str) -> &str {
+        self.theme_root(theme)
+            .map_or(self.themes_path.as_str(), |r| r.path.as_str())
+    }
+
+    pub fn theme_path(&self, theme_name: &str) -> String {
+        format!(
+            "{}/{}",
+            self.theme_root_path(theme_name).trim_end_matches('/'),
+            theme_name
+        )
+    }
+
+    /// Whether `theme` is in a read-only or protected folder.
+    pub fn is_read_only(&self, theme: &str) -> bool {
+        self.theme_root(theme).is_some_and(|r| r.is_locked())
+    }
+
+    pub fn protection(&self, theme: &str) -> Option<&Protection> {
+        self.theme_protection.get(theme)
+    }
+
+    pub fn is_protected_theme(&self, theme: &str) -> bool {
+        self.protected_themes.iter().any(|t| t.trim() == theme)
+    }
+
+    pub fn writable_roots(&self) -> Vec<String> {
+        self.theme_roots
+            .iter()
+            .filter(|r| !r.is_locked())
+            .map(|r| r.path.clone())
+            .collect()
+    }
+
+    /// Where copies of `theme` go by default: its own root if writable,
+    /// otherwise the first writable one.
+    pub fn default_save_root(&self, theme: &str) -> String {
+        match self.theme_root(theme) {
+            Some(root) if !root.is_locked() => root.path.clone(),
+            _ => self.default_import_root(),
+        }
+    }
+
+    /// Where imported themes go by default: the first writable root.
+    pub fn default_import_root(&self) -> String {
+        self.writable_roots()
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| self.themes_path.clone())
+    }
+
+    fn ensure_writable(&self, theme: &str) -> Result<(), String> {
+        match self.theme_root(theme) {
+            Some(root) if root.read_only => Err(format!(
+                "{} is in a read-only folder ({}), save it as a copy instead",
+                theme, root.path
+            )),
+            Some(root) if root.protected => Err(format!(
+                "{} is in a protected folder ({}), save it as a copy instead",
+                theme, root.path
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Refuses changing the files of a protected theme in place.
+    fn ensure_unprotected(&self, theme: &str) -> Result<(), String> {
+        match self.protection(theme) {
+            Some(protection) => Err(format!(
+                "{} {}, save it as a copy instead",
+                theme,
+                protection.describe()
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Like `ops::check_new_name` in `root`, but also refuses names used in
+    /// any other root, which would hide one of the two themes.
+    fn check_new_theme_name(
+        &self,
+        root: &str,
+        new_name: &str,
+    ) -> Result<std::path::PathBuf, String> {
+        if ops::theme_exists(&self.root_paths(), new_name.trim()) {
+            return Err(format!(
+                "A theme named '{}' already exists",
+                new_name.trim()
+            ));
+        }
+        ops::check_new_name(root, new_name)
+    }
+
+    /// Rescans every root and rebuilds `theme_names`, unsorted.
+    fn scan_theme_roots(&mut self) {
+        self.theme_roots = self
+            .root_paths()
+            .iter()
+            .map(|p| ThemeRoot::new(p, &self.protected_roots))
+            .collect();
+        let themes = scan_roots(&self.theme_roots);
+        self.theme_names = themes.iter().map(|(name, _)| name.clone()).collect();
+        self.theme_root_index = themes.into_iter().collect();
+        self.update_theme_protection();
+    }
+
+    fn update_theme_protection(&mut self) {
+        self.theme_protection = self
+            .theme_root_index
+            .iter()
+            .filter_map(|(name, &i)| {
+                let protection = theme_protection(
+                    &self.theme_roots[i],
+                    name,
+                    &self.protected_themes,
+                    &self.package_paths,
+                )?;
+                Some((name.clone(), protection))
+            })
+            .collect();
+    }
+
     pub fn load_themes(&mut self) {
         self.error_message = None;
+        self.status_message = None;
         eprintln!(
             "[DEBUG] load_themes() called with themes_path: {}",
             self.themes_path
         );
-        self.theme_names = scan_themes_dir(&self.themes_path);
+        self.scan_theme_roots();
         eprintln!("[DEBUG] Found {} themes", self.theme_names.len());
 
-        let mode = self.theme_sort_mode;
-        let last_opened = &self.theme_last_opened;
-        match mode {
-            SortMode::Name => {
-                self.theme_names
-                    .sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
-            }
-            SortMode::Color => {
-                self.theme_names.sort_by(|a, b| {
-                    let ext_a = a.rsplit('.').next().unwrap_or("").to_lowercase();
-                    let ext_b = b.rsplit('.').next().unwrap_or("").to_lowercase();
-                    ext_a.cmp(&ext_b)
-                });
-            }
-            SortMode::LastOpened => {
-                self.theme_names.sort_by(|a, b| {
-                    let order_a = last_opened.get(a).unwrap_or(&0);
-                    let order_b = last_opened.get(b).unwrap_or(&0);
-                    order_b.cmp(order_a)
-                });
-            }
-        }
+        self.theme_signatures.clear();
+        self.theme_thumbnails.clear();
+        self.sort_themes(self.theme_sort_mode);
 
         if self.theme_names.is_empty() {
             let expanded = crate::utils::path::expand_tilde(&self.themes_path);
@@ -227,6 +548,7 @@ impl OmarchyApp {
         self.selected_theme_index = None;
         self.theme_files.clear();
         self.file_cache.clear();
+        self.non_editable_files.clear();
         self.selected_file_index = None;
         self.file_content.clear();
         self.detected_colors.clear();
@@ -234,62 +556,40 @@ impl OmarchyApp {
 
     pub fn load_theme_files(&mut self) {
         self.error_message = None;
+        self.status_message = None;
+        self.merge = None;
         self.file_content.clear();
         self.detected_colors.clear();
         self.file_cache.clear();
+        self.non_editable_files.clear();
+        self.undo_stack.clear();
 
         if let Some(idx) = self.selected_theme_index {
-            if let Some(name) = self.theme_names.get(idx) {
+            if let Some(name) = self.theme_names.get(idx).cloned() {
                 self.open_counter += 1;
                 self.theme_last_opened
                     .insert(name.clone(), self.open_counter);
 
-                let theme_path = format!("{}/{}", self.themes_path.trim_end_matches('/'), name);
+                let theme_path = self.theme_path(&name);
                 eprintln!("[DEBUG] Scanning theme directory: {}", theme_path);
                 self.theme_files = scan_theme_files(&theme_path);
                 eprintln!("[DEBUG] Found {} files in theme", self.theme_files.len());
 
-                // Preload all files into cache
-                for file_name in &self.theme_files {
-                    let file_path = format!("{}/{}", theme_path, file_name);
-                    let expanded = crate::utils::path::expand_tilde(&file_path);
-                    eprintln!("[DEBUG] Preloading file: {:?}", expanded);
-                    if let Ok(content) = std::fs::read_to_string(&expanded) {
-                        let bytes = content.len();
-                        self.file_cache.insert(file_name.clone(), content);
-                        eprintln!("[DEBUG] Cached {} ({} bytes)", file_name, bytes);
-                    }
-                }
+                // Preload all text files into cache, binaries are only copied on save
+                let (file_cache, non_editable_files) =
+                    read_theme_files(&theme_path, &self.theme_files);
+                self.file_cache = file_cache;
+                self.non_editable_files = non_editable_files;
                 eprintln!("[DEBUG] Cache now contains {} files", self.file_cache.len());
 
-                let mode = self.file_sort_mode;
-                let last_opened = &self.file_last_opened;
-                match mode {
-                    SortMode::Name => {
-                        self.theme_files
-                            .sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
-                    }
-                    SortMode::Color => {
-                        self.theme_files.sort_by(|a, b| {
-                            let ext_a = a.rsplit('.').next().unwrap_or("").to_lowercase();
-                            let ext_b = b.rsplit('.').next().unwrap_or("").to_lowercase();
-                            ext_a.cmp(&ext_b)
-                        });
-                    }
-                    SortMode::LastOpened => {
-                        self.theme_files.sort_by(|a, b| {
-                            let order_a = last_opened.get(a).unwrap_or(&0);
-                            let order_b = last_opened.get(b).unwrap_or(&0);
-                            order_b.cmp(order_a)
-                        });
-                    }
-                }
+                self.sort_files(self.file_sort_mode);
 
                 if self.theme_files.is_empty() {
                     self.error_message = Some(format!("No config files found in {}", name));
                 }
 
                 self.selected_file_index = None;
+                self.save_state();
             }
         }
     }
@@ -300,9 +600,19 @@ impl OmarchyApp {
 
         if let (Some(ti), Some(fi)) = (self.selected_theme_index, self.selected_file_index) {
             if let (Some(_theme_name), Some(file_name)) =
-                (self.theme_names.get(ti), self.theme_files.get(fi))
+                (self.theme_names.get(ti), self.theme_files.get(fi).cloned())
             {
-                let ext = file_name.rsplit('.').next().unwrap_or("").to_lowercase();
+                self.open_counter += 1;
+                self.file_last_opened
+                    .insert(file_name.clone(), self.open_counter);
+                self.save_state();
+
+                if self.non_editable_files.contains(&file_name) {
+                    self.error_message = Some(format!("{} is not a text file", file_name));
+                    return;
+                }
+
+                let ext = get_extension(&file_name);
                 let ext_with_dot = format!(".{}", ext);
 
                 if let Some(config) = self.enabled_extensions.get(&ext_with_dot) {
@@ -316,7 +626,7 @@ impl OmarchyApp {
                 }
 
                 // Read from cache instead of disk
-                if let Some(content) = self.file_cache.get(file_name) {
+                if let Some(content) = self.file_cache.get(&file_name) {
                     eprintln!(
                         "[DEBUG] Loading from cache: {} ({} bytes)",
                         file_name,
@@ -334,13 +644,36 @@ impl OmarchyApp {
 
     pub fn enter_settings(&mut self) {
         self.themes_path_backup = self.themes_path.clone();
+        self.extra_theme_paths_backup = self.extra_theme_paths.clone();
         self.save_prefix_backup = self.save_prefix.clone();
+        self.apply_command_backup = self.apply_command.clone();
+        self.apply_dry_run_backup = self.apply_dry_run;
+        self.wal_colors_path_backup = self.wal_colors_path.clone();
+        self.history_enabled_backup = self.history_enabled;
+        self.protected_themes_backup = self.protected_themes.clone();
+        self.protected_roots_backup = self.protected_roots.clone();
+        self.package_paths_backup = self.package_paths.clone();
         self.show_settings = true;
     }
 
     pub fn settings_ok(&mut self) {
         self.show_settings = false;
 
+        if let Err(e) = config::save_config(&self.to_config()) {
+            self.error_message = Some(format!("Failed to save config: {}", e));
+        }
+
+        self.load_themes();
+    }
+
+    fn to_config(&self) -> config::AppConfig {
+        let non_empty = |list: &[String]| -> Vec<String> {
+            list.iter()
+                .map(|p| p.trim().to_string())
+                .filter(|p| !p.is_empty())
+                .collect()
+        };
+
         let mut extensions = HashMap::new();
         for (ext, cfg) in &self.enabled_extensions {
             extensions.insert(
@@ -352,40 +685,63 @@ impl OmarchyApp {
             );
         }
 
-        let config = config::AppConfig {
+        config::AppConfig {
             general: config::GeneralConfig {
                 themes_path: self.themes_path.clone(),
                 save_prefix: self.save_prefix.clone(),
+                extra_theme_paths: non_empty(&self.extra_theme_paths),
             },
             extensions,
-        };
-
-        if let Err(e) = config::save_config(&config) {
-            self.error_message = Some(format!("Failed to save config: {}", e));
+            apply: config::ApplyConfig {
+                command: self.apply_command.clone(),
+                dry_run: self.apply_dry_run,
+            },
+            import: config::ImportConfig {
+                wal_colors: self.wal_colors_path.clone(),
+            },
+            history: config::HistoryConfig {
+                enabled: self.history_enabled,
+            },
+            protection: config::ProtectionConfig {
+                themes: non_empty(&self.protected_themes),
+                roots: non_empty(&self.protected_roots),
+                package_paths: non_empty(&self.package_paths),
+            },
         }
-
-        self.load_themes();
     }
 
     pub fn settings_cancel(&mut self) {
         self.themes_path = self.themes_path_backup.clone();
+        self.extra_theme_paths = self.extra_theme_paths_backup.clone();
         self.save_prefix = self.save_prefix_backup.clone();
+        self.apply_command = self.apply_command_backup.clone();
+        self.apply_dry_run = self.apply_dry_run_backup;
+        self.wal_colors_path = self.wal_colors_path_backup.clone();
+        self.history_enabled = self.history_enabled_backup;
+        self.protected_themes = self.protected_themes_backup.clone();
+        self.protected_roots = self.protected_roots_backup.clone();
+        self.package_paths = self.package_paths_backup.clone();
         self.show_settings = false;
     }
 
+    /// Sorts the theme list, keeping the selection on the same theme.
     pub fn sort_themes(&mut self, mode: SortMode) {
         self.theme_sort_mode = mode;
+        let selected = self.selected_theme_name();
+        if mode == SortMode::Color {
+            self.load_theme_signatures();
+        }
         let last_opened = &self.theme_last_opened;
+        let signatures = &self.theme_signatures;
         match mode {
             SortMode::Name => {
-                self.theme_names
-                    .sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
+                self.theme_names.sort_by_key(|name| name.to_lowercase());
             }
             SortMode::Color => {
                 self.theme_names.sort_by(|a, b| {
-                    let ext_a = a.rsplit('.').next().unwrap_or("").to_lowercase();
-                    let ext_b = b.rsplit('.').next().unwrap_or("").to_lowercase();
-                    ext_a.cmp(&ext_b)
+                    signatures[a]
+                        .color_order(&signatures[b])
+                        .then_with(|| a.to_lowercase().cmp(&b.to_lowercase()))
                 });
             }
             SortMode::LastOpened => {
@@ -396,20 +752,33 @@ impl OmarchyApp {
                 });
             }
         }
+        if self.pin_favorites {
+            let favorites = &self.favorites;
+            self.theme_names
+                .sort_by_key(|name| !favorites.contains(name));
+        }
+        if selected.is_some() {
+            self.selected_theme_index =
+                selected.and_then(|name| self.theme_names.iter().position(|n| *n == name));
+        }
     }
 
+    /// Sorts the file list, keeping the selection on the same file.
     pub fn sort_files(&mut self, mode: SortMode) {
         self.file_sort_mode = mode;
+        let selected = self
+            .selected_file_index
+            .and_then(|i| self.theme_files.get(i))
+            .cloned();
         let last_opened = &self.file_last_opened;
         match mode {
             SortMode::Name => {
-                self.theme_files
-                    .sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
+                self.theme_files.sort_by_key(|name| name.to_lowercase());
             }
             SortMode::Color => {
                 self.theme_files.sort_by(|a, b| {
-                    let ext_a = a.rsplit('.').next().unwrap_or("").to_lowercase();
-                    let ext_b = b.rsplit('.').next().unwrap_or("").to_lowercase();
+                    let ext_a = get_extension(a);
+                    let ext_b = get_extension(b);
                     ext_a.cmp(&ext_b)
                 });
             }
@@ -421,6 +790,10 @@ impl OmarchyApp {
                 });
             }
         }
+        if selected.is_some() {
+            self.selected_file_index =
+                selected.and_then(|name| self.theme_files.iter().position(|n| *n == name));
+        }
     }
 
     pub fn start_color_edit(
@@ -456,13 +829,17 @@ impl OmarchyApp {
                 "[DEBUG] Changing color {} -> {} (format: {:?}) (in memory only)",
                 target.hex_text, new_formatted, target.original_format
             );
+            let target = target.clone();
+            self.push_undo();
             self.modified_colors
                 .insert(target.color_id.clone(), new_formatted.clone());
             self.has_unsaved_changes = true;
 
             let color_id = target.color_id.clone();
             let old_hex = target.hex_text.clone();
+            let file_name = target.file_name.clone();
             self.apply_color_change(&color_id, &old_hex, &new_formatted);
+            self.file_cache.insert(file_name, self.file_content.clone());
 
             self.detected_colors = detect_colors_in_content(&self.file_content);
             self.color_edit_target = None;
@@ -491,171 +868,800 @@ impl OmarchyApp {
                     // Rebuild the file content
                     let mut new_lines = lines.to_vec();
                     new_lines[line_num] = &new_line;
+                    let had_trailing_newline = self.file_content.ends_with('\n');
                     self.file_content = new_lines.join("\n");
+                    if had_trailing_newline {
+                        self.file_content.push('\n');
+                    }
                 }
             }
         }
     }
 
-    pub fn rebuild_file_content(&mut self) {
-        let mut content = String::new();
+    /// Opens the save dialog with a free name derived from `save_prefix`.
+    pub fn start_save_as_new(&mut self) {
+        if let Some(theme_name) = self.selected_theme_name() {
+            let new_name = ops::unique_theme_name(
+                &self.root_paths(),
+                &format!("{}{}", self.save_prefix, theme_name),
+            );
+            self.theme_dialog = Some(ThemeDialog::SaveAs {
+                root: self.default_save_root(&theme_name),
+                theme: theme_name,
+                new_name,
+            });
+        }
+    }
 
-        if let (Some(ti), Some(fi)) = (self.selected_theme_index, self.selected_file_index) {
-            if let (Some(theme_name), Some(file_name)) =
-                (self.theme_names.get(ti), self.theme_files.get(fi))
-            {
-                let file_path = format!(
-                    "{}/{}/{}",
-                    self.themes_path.trim_end_matches('/'),
-                    theme_name,
-                    file_name
-                );
-                let expanded = crate::utils::path::expand_tilde(&file_path);
-                if let Ok(original) = std::fs::read_to_string(&expanded) {
-                    content = original;
-                }
+    /// Writes the loaded theme with its edits as `new_theme_name` in `root`.
+    pub fn save_as_new(&mut self, new_theme_name: &str, root: &str) -> Result<(), String> {
+        if let Some(ti) = self.selected_theme_index {
+            if let Some(theme_name) = self.theme_names.get(ti) {
+                let new_theme_name = new_theme_name.trim().to_string();
+                let expanded = self.check_new_theme_name(root, &new_theme_name)?;
+                let new_theme_path = expanded.to_string_lossy().into_owned();
+
+                let original_expanded =
+                    crate::utils::path::expand_tilde(&self.theme_path(theme_name));
+
+                std::fs::create_dir_all(&expanded).map_err(|e| e.to_string())?;
+
+                self.copy_non_editable_files(&original_expanded, &expanded)?;
+                self.write_file_cache(&expanded)?;
+                let message = format!("Save as {} from {}", new_theme_name, theme_name);
+                self.record_history(&expanded, &message);
+
+                eprintln!("[DEBUG] Saved new theme: {}", new_theme_path);
+                self.has_unsaved_changes = false;
+                self.modified_colors.clear();
+                self.load_themes();
+                self.select_theme(&new_theme_name);
+                return Ok(());
             }
         }
+        Err("No theme selected".to_string())
+    }
 
-        for (color_id, new_hex) in &self.modified_colors {
-            let parts: Vec<&str> = color_id.split('_').collect();
-            if parts.len() >= 2 {
-                if let (Ok(line_num), Ok(col_start)) =
-                    (parts[0].parse::<usize>(), parts[1].parse::<usize>())
-                {
-                    let lines: Vec<char> = content.chars().collect();
-                    let mut current_line = 0;
-                    let mut line_start = 0;
-
-                    for (i, c) in lines.iter().enumerate() {
-                        if *c == '\n' {
-                            if current_line == line_num {
-                                break;
-                            }
-                            current_line += 1;
-                            line_start = i + 1;
-                        }
-                    }
+    fn push_undo(&mut self) {
+        self.undo_stack.push(EditSnapshot {
+            file_cache: self.file_cache.clone(),
+            modified_colors: self.modified_colors.clone(),
+        });
+        if self.undo_stack.len() > MAX_UNDO {
+            self.undo_stack.remove(0);
+        }
+    }
 
-                    if current_line == line_num {
-                        let mut col_end = line_start + col_start;
-                        while col_end < lines.len()
-                            && !lines[col_end].is_whitespace()
-                            && lines[col_end] != '\n'
-                        {
-                            col_end += 1;
-                        }
-
-                        if line_start + col_start <= col_end && col_end <= lines.len() {
-                            let old_color = self.detected_colors.iter().find(|c| c.id == *color_id);
-                            if let Some(old) = old_color {
-                                let old_len = old.hex_text.len();
-                                if line_start + col_start + old_len <= lines.len() {
-                                    let mut new_content: String =
-                                        lines[..line_start + col_start].iter().collect();
-                                    new_content.push_str(&new_hex);
-                                    new_content
-                                        .extend(lines[line_start + col_start + old_len..].iter());
-                                    content = new_content;
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+    pub fn undo(&mut self) {
+        if let Some(snapshot) = self.undo_stack.pop() {
+            self.file_cache = snapshot.file_cache;
+            self.modified_colors = snapshot.modified_colors;
+            self.has_unsaved_changes = !self.undo_stack.is_empty();
+            self.close_color_edit();
+            self.reload_selected_file();
+        }
+    }
+
+    /// Refreshes the colors panel after the cache was changed behind it.
+    fn reload_selected_file(&mut self) {
+        let file_name = self
+            .selected_file_index
+            .and_then(|i| self.theme_files.get(i));
+        if let Some(content) = file_name.and_then(|f| self.file_cache.get(f)) {
+            self.file_content = content.clone();
+            self.detected_colors = detect_colors_in_content(&self.file_content);
         }
+    }
 
-        self.file_content = content.clone();
-        self.detected_colors = detect_colors_in_content(&content);
+    /// Replaces every color of every cached file with `f(color)`, undoable.
+    pub fn transform_theme_colors(&mut self, f: impl FnMut(egui::Color32) -> egui::Color32) {
+        let new_cache = transform_file_cache(&self.file_cache, f);
+        self.replace_file_cache(new_cache);
     }
 
-    pub fn save_file(&mut self) -> Result<(), String> {
-        if let (Some(ti), Some(fi)) = (self.selected_theme_index, self.selected_file_index) {
-            if let (Some(theme_name), Some(file_name)) =
-                (self.theme_names.get(ti), self.theme_files.get(fi))
-            {
-                let file_path = format!(
-                    "{}/{}/{}",
-                    self.themes_path.trim_end_matches('/'),
-                    theme_name,
-                    file_name
-                );
-                let expanded = crate::utils::path::expand_tilde(&file_path);
-                std::fs::write(&expanded, &self.file_content).map_err(|e| e.to_string())?;
-                self.modified_colors.clear();
-                self.has_unsaved_changes = false;
-                return Ok(());
-            }
+    /// Swaps in recolored file contents as one undoable edit.
+    fn replace_file_cache(&mut self, new_cache: HashMap<String, String>) {
+        if new_cache == self.file_cache {
+            return;
         }
-        Err("No file selected".to_string())
+        self.push_undo();
+        self.file_cache = new_cache;
+        self.has_unsaved_changes = true;
+        self.close_color_edit();
+        self.reload_selected_file();
     }
 
-    pub fn save_as_new(&mut self) -> Result<(), String> {
-        if let Some(ti) = self.selected_theme_index {
-            if let Some(theme_name) = self.theme_names.get(ti) {
-                let new_theme_name = format!("{}{}", self.save_prefix, theme_name);
-                let new_theme_path = format!(
-                    "{}/{}",
-                    self.themes_path.trim_end_matches('/'),
-                    new_theme_name
-                );
-                let expanded = crate::utils::path::expand_tilde(&new_theme_path);
+    pub fn apply_adjustment(&mut self) {
+        let adjustment = self.adjustment;
+        self.transform_theme_colors(|c| adjustment.apply(c));
+        self.adjustment = ColorAdjustment::default();
+    }
 
-                std::fs::create_dir_all(&expanded).map_err(|e| e.to_string())?;
+    /// Recolors the loaded theme with pywal/wallust colors, undoable.
+    pub fn import_wal_colors(&mut self, path: &str, mode: RemapMode) -> Result<(), String> {
+        let wal = WalColors::load(&crate::utils::path::expand_tilde(path.trim()))?;
+        let new_cache = remap_by_role(&self.file_cache, mode, |role| {
+            Some(wal.color_for_role(role))
+        });
+        self.replace_file_cache(new_cache);
+        Ok(())
+    }
 
-                // Copy backgrounds folder from original theme
-                let original_backgrounds = format!(
-                    "{}/{}/backgrounds",
-                    self.themes_path.trim_end_matches('/'),
-                    theme_name
-                );
-                let original_backgrounds_expanded =
-                    crate::utils::path::expand_tilde(&original_backgrounds);
-                let new_backgrounds_str = format!("{}/backgrounds", expanded.display());
-                let new_backgrounds = Path::new(&new_backgrounds_str);
-
-                if original_backgrounds_expanded.exists() && original_backgrounds_expanded.is_dir()
-                {
-                    copy_dir_all(&original_backgrounds_expanded, new_backgrounds)
-                        .map_err(|e| format!("Failed to copy backgrounds: {}", e))?;
-                    eprintln!("[DEBUG] Copied backgrounds folder");
-                }
+    /// Turns the loaded dark theme into a light one in memory, then offers to
+    /// save it as a new theme. Until saved it can be reviewed in the preview
+    /// and reverted with Undo.
+    pub fn generate_light_variant(&mut self) {
+        let Some(theme_name) = self.selected_theme_name() else {
+            return;
+        };
+        let variant = LightVariant::generate(&ThemeColors::from_cache(&self.file_cache));
+        let new_cache = variant.apply(&self.file_cache);
+        self.replace_file_cache(new_cache);
+
+        let new_name = ops::unique_theme_name(&self.root_paths(), &format!("{}-light", theme_name));
+        self.theme_dialog = Some(ThemeDialog::SaveAs {
+            root: self.default_save_root(&theme_name),
+            theme: theme_name,
+            new_name,
+        });
+    }
 
-                for (file_name, content) in &self.file_cache {
-                    let file_path = format!("{}/{}", expanded.display(), file_name);
-                    std::fs::write(&file_path, content).map_err(|e| e.to_string())?;
-                }
+    /// Creates a new theme from the bundled templates filled with a
+    /// base16/base24 scheme in `root`, and selects it.
+    pub fn import_base16_theme(
+        &mut self,
+        scheme_path: &str,
+        new_name: &str,
+        root: &str,
+    ) -> Result<(), String> {
+        let scheme = Base16Scheme::load(&crate::utils::path::expand_tilde(scheme_path.trim()))?;
+        let new_name = new_name.trim();
+        let dest = self.check_new_theme_name(root, new_name)?;
+
+        std::fs::create_dir_all(&dest).map_err(|e| e.to_string())?;
+        ops::write_theme_files(&dest, &scheme.generate_theme())?;
+        eprintln!(
+            "[DEBUG] Created theme {} from scheme {}",
+            new_name, scheme.name
+        );
 
-                eprintln!("[DEBUG] Saved new theme: {}", new_theme_path);
-                self.has_unsaved_changes = false;
-                self.load_themes();
-                return Ok(());
+        self.has_unsaved_changes = false;
+        self.load_themes();
+        self.select_theme(new_name);
+        Ok(())
+    }
+
+    /// Recolors the loaded theme with a base16/base24 scheme, undoable.
+    pub fn remap_to_base16(&mut self, scheme_path: &str, mode: RemapMode) -> Result<(), String> {
+        let scheme = Base16Scheme::load(&crate::utils::path::expand_tilde(scheme_path.trim()))?;
+        let new_cache = scheme.remap_cache(&self.file_cache, mode);
+        self.replace_file_cache(new_cache);
+        Ok(())
+    }
+
+    /// Writes every cached file below `theme_dir`, recreating the subfolders
+    /// the files came from.
+    fn write_file_cache(&self, theme_dir: &Path) -> Result<(), String> {
+        ops::write_theme_files(theme_dir, &self.file_cache)
+    }
+
+    /// Copies binary and other non-text files byte-for-byte, e.g. `backgrounds/`.
+    fn copy_non_editable_files(&self, src_dir: &Path, dst_dir: &Path) -> Result<(), String> {
+        for file_name in &self.non_editable_files {
+            let dest = dst_dir.join(file_name);
+            if let Some(parent) = dest.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
             }
+            std::fs::copy(src_dir.join(file_name), &dest)
+                .map_err(|e| format!("Failed to copy {}: {}", file_name, e))?;
+        }
+        Ok(())
+    }
+
+    /// Commits a just written theme folder when version history is enabled.
+    /// A failed commit is reported but does not undo the save.
+    fn record_history(&mut self, theme_dir: &Path, message: &str) {
+        if !self.history_enabled {
+            return;
+        }
+        // Commits in a theme installed from git would block pulling updates
+        if git::origin_url(theme_dir).is_some() {
+            eprintln!("[DEBUG] Not recording history in {}", theme_dir.display());
+            return;
+        }
+        match git::commit_theme(theme_dir, message) {
+            Ok(Some(id)) => eprintln!("[DEBUG] Committed {} in {}", id, theme_dir.display()),
+            Ok(None) => {}
+            Err(e) => self.error_message = Some(format!("Saved, but {}", e)),
+        }
+        if self.history.is_some() {
+            self.refresh_history();
         }
-        Err("No theme selected".to_string())
     }
 
     pub fn overwrite_theme(&mut self) -> Result<(), String> {
         if let Some(ti) = self.selected_theme_index {
             if let Some(theme_name) = self.theme_names.get(ti) {
-                let theme_path =
-                    format!("{}/{}", self.themes_path.trim_end_matches('/'), theme_name);
+                let theme_path = self.theme_path(theme_name);
                 let expanded = crate::utils::path::expand_tilde(&theme_path);
 
                 if !expanded.exists() {
                     return Err(format!("Theme folder does not exist: {}", theme_path));
                 }
-
-                for (file_name, content) in &self.file_cache {
-                    let file_path = format!("{}/{}", expanded.display(), file_name);
-                    std::fs::write(&file_path, content).map_err(|e| e.to_string())?;
+                self.ensure_writable(theme_name)?;
+                self.ensure_unprotected(theme_name)?;
+
+                if let Err(e) = self.write_file_cache(&expanded) {
+                    // The folder passed the access check but refused the
+                    // write, treat it as read-only until the next scan
+                    let message = format!(
+                        "Failed to save {}: {}, save it as a copy instead",
+                        theme_name, e
+                    );
+                    if let Some(&i) = self.theme_root_index.get(theme_name.as_str()) {
+                        self.theme_roots[i].read_only = true;
+                    }
+                    return Err(message);
                 }
+                self.theme_signatures.remove(theme_name);
+                self.theme_thumbnails.remove(theme_name);
+                let message = format!("Overwrite {}", theme_name);
+                self.record_history(&expanded, &message);
 
                 eprintln!("[DEBUG] Overwrote theme: {}", theme_path);
                 self.has_unsaved_changes = false;
+                self.undo_stack.clear();
                 return Ok(());
             }
         }
         Err("No theme selected".to_string())
     }
+
+    pub fn selected_theme_name(&self) -> Option<String> {
+        self.selected_theme_index
+            .and_then(|i| self.theme_names.get(i))
+            .cloned()
+    }
+
+    /// Selects a theme by name after `load_themes` has reset the selection.
+    pub fn select_theme(&mut self, name: &str) {
+        if let Some(idx) = self.theme_names.iter().position(|n| n == name) {
+            self.selected_theme_index = Some(idx);
+            self.load_theme_files();
+        }
+    }
+
+    /// Rescans the theme names but keeps the loaded theme and its in-memory
+    /// edits, re-pointing the selection at `selected` if it still exists.
+    fn refresh_theme_names(&mut self, selected: Option<&str>) {
+        self.scan_theme_roots();
+        self.theme_signatures.clear();
+        self.theme_thumbnails.clear();
+        self.sort_themes(self.theme_sort_mode);
+        self.selected_theme_index =
+            selected.and_then(|name| self.theme_names.iter().position(|n| n == name));
+    }
+
+    pub fn rename_theme(&mut self, theme: &str, new_name: &str) -> Result<(), String> {
+        let new_name = new_name.trim();
+        self.ensure_writable(theme)?;
+        self.ensure_unprotected(theme)?;
+        let root = self.theme_root_path(theme);
+        self.check_new_theme_name(root, new_name)?;
+        ops::rename_theme(root, theme, new_name)?;
+        eprintln!("[DEBUG] Renamed theme {} -> {}", theme, new_name);
+
+        if let Some(order) = self.theme_last_opened.remove(theme) {
+            self.theme_last_opened.insert(new_name.to_string(), order);
+        }
+        if self.favorites.remove(theme) {
+            self.favorites.insert(new_name.to_string());
+        }
+        if let Some(tags) = self.theme_tags.remove(theme) {
+            self.theme_tags.insert(new_name.to_string(), tags);
+        }
+        let selected = self.selected_theme_name().map(|name| {
+            if name == theme {
+                new_name.to_string()
+            } else {
+                name
+            }
+        });
+        self.refresh_theme_names(selected.as_deref());
+        Ok(())
+    }
+
+    pub fn duplicate_theme(&mut self, theme: &str, new_name: &str) -> Result<(), String> {
+        let new_name = new_name.trim();
+        let src_root = self.theme_root_path(theme);
+        let dest_root = self.default_save_root(theme);
+        self.check_new_theme_name(&dest_root, new_name)?;
+        ops::duplicate_theme(src_root, theme, &dest_root, new_name)?;
+        eprintln!("[DEBUG] Duplicated theme {} -> {}", theme, new_name);
+
+        let selected = self.selected_theme_name();
+        self.refresh_theme_names(selected.as_deref());
+        Ok(())
+    }
+
+    pub fn delete_theme(&mut self, theme: &str) -> Result<(), String> {
+        self.ensure_writable(theme)?;
+        self.ensure_unprotected(theme)?;
+        let trashed = ops::trash_theme(self.theme_root_path(theme), theme)?;
+        eprintln!("[DEBUG] Moved theme {} to {}", theme, trashed.display());
+
+        self.theme_last_opened.remove(theme);
+        self.favorites.remove(theme);
+        self.theme_tags.remove(theme);
+        let selected = self.selected_theme_name();
+        if selected.as_deref() == Some(theme) {
+            self.has_unsaved_changes = false;
+            self.load_themes();
+        } else {
+            self.refresh_theme_names(selected.as_deref());
+        }
+        Ok(())
+    }
+
+    pub fn toggle_favorite(&mut self, theme: &str) {
+        if !self.favorites.remove(theme) {
+            self.favorites.insert(theme.to_string());
+        }
+        self.sort_themes(self.theme_sort_mode);
+        self.save_state();
+    }
+
+    /// Replaces the tags of `theme` with the comma separated `tags`.
+    pub fn set_theme_tags(&mut self, theme: &str, tags: &str) {
+        let mut list: Vec<String> = Vec::new();
+        for tag in tags.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            if !list.iter().any(|t| t == tag) {
+                list.push(tag.to_string());
+            }
+        }
+        if list.is_empty() {
+            self.theme_tags.remove(theme);
+        } else {
+            self.theme_tags.insert(theme.to_string(), list);
+        }
+        if let Some(filter) = &self.tag_filter {
+            if !self.all_tags().contains(filter) {
+                self.tag_filter = None;
+            }
+        }
+        self.save_state();
+    }
+
+    /// Every tag in use, sorted.
+    pub fn all_tags(&self) -> Vec<String> {
+        let tags: BTreeSet<&String> = self.theme_tags.values().flatten().collect();
+        tags.into_iter().cloned().collect()
+    }
+
+    pub fn has_tag(&self, theme: &str, tag: &str) -> bool {
+        self.theme_tags
+            .get(theme)
+            .is_some_and(|tags| tags.iter().any(|t| t == tag))
+    }
+
+    /// Adds `theme` to or removes it from the protected themes in the config.
+    pub fn toggle_protected(&mut self, theme: &str) {
+        if self.is_protected_theme(theme) {
+            self.protected_themes.retain(|t| t.trim() != theme);
+        } else {
+            self.protected_themes.push(theme.to_string());
+        }
+        self.save_protected_themes();
+        self.update_theme_protection();
+    }
+
+    fn save_protected_themes(&mut self) {
+        self.protected_themes_backup = self.protected_themes.clone();
+        if let Err(e) = config::save_config(&self.to_config()) {
+            self.error_message = Some(format!("Failed to save config: {}", e));
+        }
+    }
+
+    /// Runs the configured apply command for the selected theme as saved on disk.
+    pub fn apply_theme(&mut self) -> Result<(), String> {
+        let theme_name = self.selected_theme_name().ok_or("No theme selected")?;
+        let theme_path = crate::utils::path::expand_tilde(&self.theme_path(&theme_name));
+        if self.apply_command.trim().is_empty() {
+            return Err("No apply command configured".to_string());
+        }
+        if self.apply_job.is_some() {
+            return Err("The apply command is still running".to_string());
+        }
+        self.apply_output = None;
+        self.apply_job = Some(spawn_apply_command(
+            &self.apply_command,
+            &theme_name,
+            &theme_path.to_string_lossy(),
+            self.apply_dry_run,
+        ));
+        Ok(())
+    }
+
+    /// Picks up the result of a running apply command once it is done.
+    pub fn poll_apply(&mut self) {
+        let Some(job) = &self.apply_job else {
+            return;
+        };
+        match job.result.try_recv() {
+            Ok(Ok(output)) => {
+                self.apply_output = Some(output);
+                self.apply_job = None;
+            }
+            Ok(Err(e)) => {
+                self.error_message = Some(e);
+                self.apply_job = None;
+            }
+            Err(std::sync::mpsc::TryRecvError::Empty) => {}
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                self.error_message = Some("The apply command stopped unexpectedly".to_string());
+                self.apply_job = None;
+            }
+        }
+    }
+
+    /// Text files of `theme`, including unsaved edits if it is the loaded one.
+    fn theme_cache(&self, theme: &str) -> HashMap<String, String> {
+        if self.selected_theme_name().as_deref() == Some(theme) {
+            self.file_cache.clone()
+        } else {
+            load_theme_cache(&self.theme_path(theme))
+        }
+    }
+
+    fn theme_colors(&self, theme: &str) -> ThemeColors {
+        ThemeColors::from_cache(&self.theme_cache(theme))
+    }
+
+    /// Opens the merge window to take files or colors from `source` into the
+    /// selected theme.
+    pub fn start_merge_from(&mut self, source: &str) {
+        if self.selected_theme_index.is_none() {
+            return;
+        }
+        let source_cache = load_theme_cache(&self.theme_path(source));
+        self.merge = Some(MergePlan::new(source, source_cache, &self.file_cache));
+    }
+
+    /// Applies the merge selection to the loaded theme as one undoable edit.
+    pub fn apply_merge(&mut self) {
+        let Some(plan) = self.merge.take() else {
+            return;
+        };
+        let merged = plan.apply(&self.file_cache);
+        let mut added: Vec<String> = merged
+            .keys()
+            .filter(|f| !self.theme_files.contains(f))
+            .cloned()
+            .collect();
+        added.sort();
+        self.theme_files.extend(added);
+        self.replace_file_cache(merged);
+    }
+
+    /// Opens the comparison of the selected theme (with its edits) and `other`.
+    pub fn compare_with_selected(&mut self, other: &str) {
+        let Some(selected) = self.selected_theme_name() else {
+            return;
+        };
+        let left_dir = crate::utils::path::expand_tilde(&self.theme_path(&selected));
+        let right_dir = crate::utils::path::expand_tilde(&self.theme_path(other));
+        self.compare = Some(compare_themes(
+            (&selected, &left_dir, &self.file_cache),
+            (other, &right_dir),
+        ));
+    }
+
+    /// Loads the thumbnail of `theme` from the disk cache, building it if the
+    /// theme changed, and uploads the wallpaper as a texture.
+    pub fn load_theme_thumbnail(&mut self, ctx: &egui::Context, theme: &str) {
+        let thumb = load_thumbnail(&self.theme_path(theme));
+        let wallpaper = thumb.wallpaper.map(|image| {
+            ctx.load_texture(
+                format!("thumbnail-{}", theme),
+                image,
+                egui::TextureOptions::LINEAR,
+            )
+        });
+        self.theme_thumbnails.insert(
+            theme.to_string(),
+            LoadedThumbnail {
+                palette: thumb.palette,
+                wallpaper,
+            },
+        );
+    }
+
+    /// Computes the missing palette signatures of the listed themes.
+    fn load_theme_signatures(&mut self) {
+        let missing: Vec<String> = self
+            .theme_names
+            .iter()
+            .filter(|name| !self.theme_signatures.contains_key(*name))
+            .cloned()
+            .collect();
+        for name in missing {
+            let colors = ThemeColors::from_cache(&load_theme_cache(&self.theme_path(&name)));
+            self.theme_signatures
+                .insert(name, PaletteSignature::new(&colors));
+        }
+    }
+
+    /// Opens the list of themes closest in color to `theme`.
+    pub fn find_similar_themes(&mut self, theme: &str) {
+        self.load_theme_signatures();
+        let target = PaletteSignature::new(&self.theme_colors(theme));
+        if target.is_empty() {
+            self.error_message = Some(format!("No colors found in {}", theme));
+            return;
+        }
+        let others = self
+            .theme_signatures
+            .iter()
+            .filter(|(name, _)| name.as_str() != theme);
+        let ranked = rank_similar(&target, others)
+            .into_iter()
+            .map(|(name, distance)| {
+                let signature = self.theme_signatures[&name].clone();
+                (name, signature, distance)
+            })
+            .collect();
+        self.similar = Some(SimilarThemes {
+            theme: theme.to_string(),
+            ranked,
+        });
+    }
+
+    /// Runs the query of the color search window against every theme.
+    pub fn search_color_in_themes(&mut self) {
+        let Some(search) = &self.color_search else {
+            return;
+        };
+        let Some(query) = ColorQuery::parse(&search.query) else {
+            self.error_message = Some(format!("Invalid color query: {}", search.query));
+            return;
+        };
+        let results: Vec<ThemeSearchResult> = self
+            .theme_names
+            .iter()
+            .filter_map(|theme| {
+                let matches = search_theme(&self.theme_cache(theme), &query);
+                (!matches.is_empty()).then(|| ThemeSearchResult {
+                    theme: theme.clone(),
+                    matches,
+                })
+            })
+            .collect();
+        eprintln!(
+            "[DEBUG] Color search '{}' matched {} themes",
+            search.query,
+            results.len()
+        );
+        if let Some(search) = &mut self.color_search {
+            search.results = Some(results);
+        }
+    }
+
+    /// Selects `theme` and opens `file` in the colors panel.
+    pub fn open_theme_file(&mut self, theme: &str, file: &str) {
+        if self.selected_theme_name().as_deref() != Some(theme) {
+            self.select_theme(theme);
+        }
+        if let Some(idx) = self.theme_files.iter().position(|f| f == file) {
+            self.selected_file_index = Some(idx);
+            self.load_file_content();
+        }
+    }
+
+    pub fn export_theme_palette(
+        &mut self,
+        theme: &str,
+        format: PaletteFormat,
+        path: &str,
+    ) -> Result<(), String> {
+        let content = export_palette(theme, &self.theme_colors(theme), format);
+        let path = crate::utils::path::expand_tilde(path.trim());
+        std::fs::write(&path, content)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        eprintln!("[DEBUG] Exported {} palette to {}", theme, path.display());
+        Ok(())
+    }
+
+    /// Packs the theme as saved on disk into a `.tar.gz`.
+    pub fn export_theme_archive(&mut self, theme: &str, dest: &str) -> Result<(), String> {
+        let theme_dir = crate::utils::path::expand_tilde(&self.theme_path(theme));
+        let dest = crate::utils::path::expand_tilde(dest.trim());
+        archive::export_theme_archive(&theme_dir, theme, &dest)?;
+        eprintln!("[DEBUG] Exported theme {} to {}", theme, dest.display());
+        Ok(())
+    }
+
+    /// Unpacks a theme archive into `root` under `new_name` and selects it.
+    pub fn import_theme_archive(
+        &mut self,
+        path: &str,
+        new_name: &str,
+        root: &str,
+    ) -> Result<(), String> {
+        let path = crate::utils::path::expand_tilde(path.trim());
+        let info = archive::inspect_archive(&path)?;
+        let new_name = new_name.trim();
+        let dest = self.check_new_theme_name(root, new_name)?;
+
+        archive::unpack_archive(&path, &info, &dest)?;
+        eprintln!("[DEBUG] Imported {} as theme {}", path.display(), new_name);
+
+        self.has_unsaved_changes = false;
+        self.load_themes();
+        self.select_theme(new_name);
+        Ok(())
+    }
+
+    /// Starts cloning a theme repository into `root` in the background, see
+    /// `poll_git_job`.
+    pub fn import_theme_from_git(
+        &mut self,
+        url: &str,
+        new_name: &str,
+        root: &str,
+    ) -> Result<(), String> {
+        if self.git_job.is_some() {
+            return Err("Another git transfer is still running".to_string());
+        }
+        let new_name = new_name.trim();
+        let dest = self.check_new_theme_name(root, new_name)?;
+        self.git_job = Some(git::spawn_clone(url, dest, new_name));
+        Ok(())
+    }
+
+    pub fn theme_origin_url(&self, theme: &str) -> Option<String> {
+        git::origin_url(&crate::utils::path::expand_tilde(&self.theme_path(theme)))
+    }
+
+    /// Starts pulling updates of a theme in the background, see
+    /// `poll_git_job`.
+    pub fn pull_theme_updates(&mut self, theme: &str) -> Result<(), String> {
+        if self.git_job.is_some() {
+            return Err("Another git transfer is still running".to_string());
+        }
+        let is_selected = self.selected_theme_name().as_deref() == Some(theme);
+        if is_selected && self.has_unsaved_changes {
+            return Err("Save or undo your changes before pulling updates".to_string());
+        }
+        self.ensure_writable(theme)?;
+        self.ensure_unprotected(theme)?;
+        let theme_dir = crate::utils::path::expand_tilde(&self.theme_path(theme));
+        self.git_job = Some(git::spawn_pull(theme_dir, theme));
+        Ok(())
+    }
+
+    /// Finishes a clone or pull once its worker is done: selects the new
+    /// theme, or reloads the updated one if it is open.
+    pub fn poll_git_job(&mut self) {
+        let Some(job) = &self.git_job else {
+            return;
+        };
+        let result = match job.result.try_recv() {
+            Ok(result) => result,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                Err("The git transfer stopped unexpectedly".to_string())
+            }
+        };
+        let kind = job.kind.clone();
+        self.git_job = None;
+
+        let outcome = match result {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                self.error_message = Some(e);
+                return;
+            }
+        };
+        match kind {
+            GitJobKind::Clone { theme, .. } => {
+                if !self.has_unsaved_changes {
+                    self.load_themes();
+                    self.select_theme(&theme);
+                } else {
+                    let selected = self.selected_theme_name();
+                    self.refresh_theme_names(selected.as_deref());
+                }
+                self.status_message = Some(format!("{}: {}", theme, outcome));
+            }
+            GitJobKind::Pull { theme } => {
+                eprintln!("[DEBUG] Pulled {}: {}", theme, outcome);
+                self.theme_signatures.remove(&theme);
+                self.theme_thumbnails.remove(&theme);
+                let is_selected = self.selected_theme_name().as_deref() == Some(theme.as_str());
+                if is_selected && !self.has_unsaved_changes {
+                    self.load_theme_files();
+                }
+                self.status_message = Some(format!("{}: {}", theme, outcome));
+            }
+        }
+    }
+
+    /// Opens the history window for the selected theme.
+    pub fn open_history(&mut self) {
+        if let Some(theme) = self.selected_theme_name() {
+            self.history = Some(HistoryView {
+                theme,
+                origin: None,
+                commits: vec![],
+                selected: None,
+                diff: String::new(),
+            });
+            self.refresh_history();
+        }
+    }
+
+    /// Re-reads the commits of the selected theme, e.g. after a save or a
+    /// theme switch.
+    pub fn refresh_history(&mut self) {
+        let Some(theme) = self.selected_theme_name() else {
+            self.history = None;
+            return;
+        };
+        let theme_dir = crate::utils::path::expand_tilde(&self.theme_path(&theme));
+        let commits = match git::theme_history(&theme_dir) {
+            Ok(commits) => commits,
+            Err(e) => {
+                self.error_message = Some(e);
+                vec![]
+            }
+        };
+        self.history = Some(HistoryView {
+            theme,
+            origin: git::origin_url(&theme_dir),
+            commits,
+            selected: None,
+            diff: String::new(),
+        });
+    }
+
+    pub fn select_history_commit(&mut self, index: usize) {
+        let Some(history) = &self.history else {
+            return;
+        };
+        let Some(commit) = history.commits.get(index) else {
+            return;
+        };
+        let theme_dir = crate::utils::path::expand_tilde(&self.theme_path(&history.theme));
+        let diff = git::commit_diff(&theme_dir, &commit.id).unwrap_or_else(|e| e);
+        if let Some(history) = &mut self.history {
+            history.selected = Some(index);
+            history.diff = diff;
+        }
+    }
+
+    /// Restores the theme files of an earlier commit as a new commit and
+    /// reloads the theme.
+    pub fn revert_to_commit(&mut self, id: &str) -> Result<(), String> {
+        let theme = self.selected_theme_name().ok_or("No theme selected")?;
+        if self.has_unsaved_changes {
+            return Err("Save or undo your changes before reverting".to_string());
+        }
+        self.ensure_writable(&theme)?;
+        self.ensure_unprotected(&theme)?;
+        let theme_dir = crate::utils::path::expand_tilde(&self.theme_path(&theme));
+        if git::origin_url(&theme_dir).is_some() {
+            return Err("Themes installed from git follow upstream, use Pull updates".to_string());
+        }
+        git::revert_theme_to(&theme_dir, id)?;
+        eprintln!("[DEBUG] Reverted {} to {}", theme, id);
+
+        self.load_theme_files();
+        self.refresh_history();
+        Ok(())
+    }
+
+    pub fn open_theme_folder(&self, theme: &str) -> Result<(), String> {
+        let path = crate::utils::path::expand_tilde(&self.theme_path(theme));
+        ops::open_folder(&path)
+    }
 }
@@ -0,0 +1,105 @@
+use crate::theme::contrast::{check_theme_contrast, ContrastLevel};
+use crate::theme::scanner::load_theme_cache;
+use crate::theme::semantic::ThemeColors;
+use crate::utils::path::expand_tilde;
+
+const USAGE: &str = "Usage:
+  omarchy-theme-maker                          Start the editor
+  omarchy-theme-maker --check-contrast <theme-dir>... [--min <ratio>] [--apca]
+      Check WCAG contrast of the text/background pairs of each theme.
+      Exits with status 1 if a text pair is below --min (default 4.5).";
+
+/// Runs a command line action. Returns the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    match args.first().map(|a| a.as_str()) {
+        Some("--check-contrast") => check_contrast(&args[1..]),
+        Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            0
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            2
+        }
+    }
+}
+
+fn check_contrast(args: &[String]) -> i32 {
+    let mut min_ratio = 4.5;
+    let mut show_apca = false;
+    let mut themes = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--min" => match iter.next().and_then(|v| v.parse::<f32>().ok()) {
+                Some(v) => min_ratio = v,
+                None => {
+                    eprintln!("--min expects a number");
+                    return 2;
+                }
+            },
+            "--apca" => show_apca = true,
+            _ => themes.push(arg.clone()),
+        }
+    }
+
+    if themes.is_empty() {
+        eprintln!("{}", USAGE);
+        return 2;
+    }
+
+    let mut failed = false;
+    for theme in &themes {
+        if !expand_tilde(theme).is_dir() {
+            eprintln!("{}: not a directory", theme);
+            failed = true;
+            continue;
+        }
+
+        println!("{}", theme);
+        let colors = ThemeColors::from_cache(&load_theme_cache(theme));
+        let results = check_theme_contrast(&colors);
+        if results.is_empty() {
+            println!("  no known foreground/background pairs found");
+        }
+
+        for result in &results {
+            let pass = result.ratio >= min_ratio;
+            let status = if !result.required {
+                "info"
+            } else if pass {
+                "ok"
+            } else {
+                "FAIL"
+            };
+            if result.required && !pass {
+                failed = true;
+            }
+
+            let level = result.level();
+            let mut line = format!(
+                "  [{:>4}] {:<24} {:>5.2}:1 {:<8} {}",
+                status,
+                result.label,
+                result.ratio,
+                if level == ContrastLevel::Fail {
+                    "-"
+                } else {
+                    level.label()
+                },
+                result.file
+            );
+            if show_apca {
+                line.push_str(&format!("  Lc {:.1}", result.apca));
+            }
+            println!("{}", line);
+        }
+    }
+
+    if failed {
+        1
+    } else {
+        0
+    }
+}
@@ -13,6 +13,14 @@ pub struct ExtensionSetting {
 pub struct AppConfig {
     pub general: GeneralConfig,
     pub extensions: HashMap<String, ExtensionSetting>,
+    #[serde(default)]
+    pub apply: ApplyConfig,
+    #[serde(default)]
+    pub import: ImportConfig,
+    #[serde(default)]
+    pub history: HistoryConfig,
+    #[serde(default)]
+    pub protection: ProtectionConfig,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -21,6 +29,10 @@ pub struct GeneralConfig {
     pub themes_path: String,
     #[serde(rename = "save_prefix", default)]
     pub save_prefix: String,
+    /// More folders to list themes from after `themes_path`, e.g. a system
+    /// theme folder or a dotfiles checkout
+    #[serde(default)]
+    pub extra_theme_paths: Vec<String>,
 }
 
 impl Default for GeneralConfig {
@@ -34,6 +46,74 @@ impl Default for GeneralConfig {
                 })
                 .unwrap_or_else(|| "/home/your/.config/omarchy/themes".to_string()),
             save_prefix: "new-".to_string(),
+            extra_theme_paths: vec![],
+        }
+    }
+}
+
+/// Command run by "Apply". `{name}` and `{path}` are replaced by the shell-quoted
+/// theme name and theme folder.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApplyConfig {
+    pub command: String,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl Default for ApplyConfig {
+    fn default() -> Self {
+        Self {
+            command: "omarchy-theme-set {name}".to_string(),
+            dry_run: false,
+        }
+    }
+}
+
+/// Where imports look for files generated by other tools.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImportConfig {
+    /// pywal / wallust `colors.json`
+    pub wal_colors: String,
+}
+
+impl Default for ImportConfig {
+    fn default() -> Self {
+        Self {
+            wal_colors: "~/.cache/wal/colors.json".to_string(),
+        }
+    }
+}
+
+/// Commit each saved or overwritten theme to a git repository in its folder.
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+pub struct HistoryConfig {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Themes that are never changed in place, only saved as a copy.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProtectionConfig {
+    /// Theme names, in any theme folder
+    pub themes: Vec<String>,
+    /// Theme folders whose themes are all protected
+    pub roots: Vec<String>,
+    /// Package-managed locations: a theme folder that is a symlink into one
+    /// of these is replaced on update, so it is protected too
+    pub package_paths: Vec<String>,
+}
+
+impl Default for ProtectionConfig {
+    fn default() -> Self {
+        Self {
+            themes: vec![],
+            roots: vec![],
+            package_paths: vec![
+                "~/.local/share/omarchy".to_string(),
+                "/usr/share".to_string(),
+                "/opt".to_string(),
+            ],
         }
     }
 }
@@ -125,7 +205,7 @@ fn get_config_path() -> Option<PathBuf> {
     None
 }
 
-fn get_config_dir() -> PathBuf {
+pub fn get_config_dir() -> PathBuf {
     if let Some(config_dir) = dirs::config_dir() {
         config_dir.join("omarchy-theme-maker")
     } else if let Some(project_dir) = get_project_dir() {
@@ -148,6 +228,10 @@ pub fn load_config() -> (AppConfig, Option<String>) {
         AppConfig {
             general: GeneralConfig::default(),
             extensions: get_default_extensions(),
+            apply: ApplyConfig::default(),
+            import: ImportConfig::default(),
+            history: HistoryConfig::default(),
+            protection: ProtectionConfig::default(),
         },
         None,
     )
@@ -1,17 +1,30 @@
 mod app;
+mod cli;
 mod config;
+mod state;
 mod theme;
 mod ui;
 mod utils;
 
 use app::OmarchyApp;
 use eframe::egui;
-use ui::{ui_colors_panel, ui_files_panel, ui_settings_panel, ui_themes_panel};
+use ui::{
+    ui_adjust_window, ui_apply_output, ui_color_search_window, ui_colors_panel, ui_compare_window,
+    ui_files_panel, ui_git_job_window, ui_history_window, ui_merge_window, ui_preview_panel,
+    ui_settings_panel, ui_similar_window, ui_theme_dialog, ui_themes_panel,
+};
+use utils::cvd::CvdMode;
 
 fn main() -> eframe::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
+
+    let window_size = state::load_state().window_size.unwrap_or([1000.0, 650.0]);
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
-            .with_inner_size([1000.0, 650.0])
+            .with_inner_size(window_size)
             .with_min_inner_size([700.0, 400.0]),
         ..Default::default()
     };
@@ -25,6 +38,14 @@ fn main() -> eframe::Result<()> {
 impl eframe::App for OmarchyApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         ctx.set_visuals(egui::Visuals::dark());
+        if let Some(rect) = ctx.input(|i| i.viewport().inner_rect) {
+            self.window_size = Some([rect.width(), rect.height()]);
+        }
+        self.poll_apply();
+        self.poll_git_job();
+        if self.apply_job.is_some() || self.git_job.is_some() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
 
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             ui.horizontal(|ui| {
@@ -33,11 +54,61 @@ impl eframe::App for OmarchyApp {
                     self.enter_settings();
                 }
 
+                if ui.selectable_label(self.show_preview, "Preview").clicked() {
+                    self.show_preview = !self.show_preview;
+                }
+
+                egui::ComboBox::from_id_salt("cvd_mode")
+                    .selected_text(self.cvd_mode.label())
+                    .show_ui(ui, |ui| {
+                        for mode in CvdMode::ALL {
+                            ui.selectable_value(&mut self.cvd_mode, mode, mode.label());
+                        }
+                    });
+
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     let has_theme = self.selected_theme_index.is_some();
                     let has_changes = self.has_unsaved_changes;
 
-                    let overwrite_btn = ui.add_enabled(has_theme, egui::Button::new("Overwrite"));
+                    let undo_btn =
+                        ui.add_enabled(!self.undo_stack.is_empty(), egui::Button::new("Undo"));
+                    if undo_btn.clicked()
+                        || (!self.undo_stack.is_empty()
+                            && ui.input(|i| i.modifiers.command && i.key_pressed(egui::Key::Z)))
+                    {
+                        self.undo();
+                    }
+
+                    let adjust_btn = ui.add_enabled(has_theme, egui::Button::new("Adjust…"));
+                    if adjust_btn.clicked() {
+                        self.show_adjust = true;
+                    }
+
+                    let history_btn = ui.add_enabled(has_theme, egui::Button::new("History"));
+                    if history_btn.clicked() {
+                        self.open_history();
+                    }
+
+                    ui.separator();
+
+                    let locked_reason = self.selected_theme_name().and_then(|name| {
+                        if let Some(protection) = self.protection(&name) {
+                            Some(format!("{} {}", name, protection.describe()))
+                        } else if self.is_read_only(&name) {
+                            Some(format!("{} is in a read-only folder", name))
+                        } else {
+                            None
+                        }
+                    });
+                    let overwrite_btn = ui.add_enabled(
+                        has_theme && locked_reason.is_none(),
+                        egui::Button::new("Overwrite"),
+                    );
+                    let overwrite_btn = match &locked_reason {
+                        Some(reason) => overwrite_btn
+                            .on_disabled_hover_text(format!("{}, use Save to make a copy", reason)),
+                        None => overwrite_btn,
+                    };
                     if overwrite_btn.clicked() {
                         if let Err(e) = self.overwrite_theme() {
                             self.error_message = Some(e);
@@ -47,7 +118,22 @@ impl eframe::App for OmarchyApp {
                     let save_btn =
                         ui.add_enabled(has_theme && has_changes, egui::Button::new("Save"));
                     if save_btn.clicked() {
-                        if let Err(e) = self.save_as_new() {
+                        self.start_save_as_new();
+                    }
+
+                    let applying = self.apply_job.is_some();
+                    let apply_btn = ui
+                        .add_enabled(
+                            has_theme && !has_changes && !applying,
+                            egui::Button::new("Apply"),
+                        )
+                        .on_disabled_hover_text(if applying {
+                            "The apply command is running"
+                        } else {
+                            "Save or overwrite the theme before applying"
+                        });
+                    if apply_btn.clicked() {
+                        if let Err(e) = self.apply_theme() {
                             self.error_message = Some(e);
                         }
                     }
@@ -63,14 +149,31 @@ impl eframe::App for OmarchyApp {
             }
 
             ui_themes_panel(ctx, self);
+            ui_theme_dialog(ctx, self);
+            ui_apply_output(ctx, self);
+            ui_git_job_window(ctx, self);
+            ui_adjust_window(ctx, self);
+            ui_history_window(ctx, self);
+            ui_compare_window(ctx, self);
+            ui_merge_window(ctx, self);
+            ui_color_search_window(ctx, self);
+            ui_similar_window(ctx, self);
 
             if self.selected_theme_index.is_some() {
                 ui_files_panel(ctx, self);
             }
 
+            if self.show_preview && self.selected_theme_index.is_some() {
+                ui_preview_panel(ctx, self);
+            }
+
             if self.selected_file_index.is_some() {
                 ui_colors_panel(ctx, self);
             }
         }
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.save_state();
+    }
 }
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::app::SortMode;
+use crate::config::get_config_dir;
+
+/// UI state remembered between runs, kept apart from the user's settings
+/// in `state.toml` next to `config.toml`.
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct AppState {
+    pub theme_sort_mode: SortMode,
+    /// List starred themes first, whatever the sort mode
+    pub pin_favorites: bool,
+    pub file_sort_mode: SortMode,
+    pub open_counter: u64,
+    pub theme_last_opened: HashMap<String, u64>,
+    pub file_last_opened: HashMap<String, u64>,
+    pub last_theme: Option<String>,
+    pub last_file: Option<String>,
+    pub favorites: BTreeSet<String>,
+    /// Free-form tags per theme name
+    pub theme_tags: BTreeMap<String, Vec<String>>,
+    /// Inner window size in points
+    pub window_size: Option<[f32; 2]>,
+}
+
+pub fn load_state() -> AppState {
+    let path = get_config_dir().join("state.toml");
+    match std::fs::read_to_string(&path) {
+        Ok(content) => toml::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("[DEBUG] Ignoring invalid {:?}: {}", path, e);
+            AppState::default()
+        }),
+        Err(_) => AppState::default(),
+    }
+}
+
+pub fn save_state(state: &AppState) -> Result<(), String> {
+    let config_dir = get_config_dir();
+    std::fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
+
+    let content = toml::to_string_pretty(state).map_err(|e| e.to_string())?;
+    std::fs::write(config_dir.join("state.toml"), content).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
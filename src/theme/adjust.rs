@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use egui::Color32;
+
+use crate::utils::color::{format_like, map_colors_in_content};
+use crate::utils::colorspace::{from_oklab, from_oklch, to_oklab, to_oklch};
+
+/// Theme-wide color transform, done in OKLCH so hue and lightness changes
+/// look even across the palette.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorAdjustment {
+    /// Degrees added to the hue
+    pub hue_shift: f32,
+    /// Chroma multiplier
+    pub saturation: f32,
+    /// Lightness multiplier
+    pub lightness: f32,
+    /// -1.0 (cool, towards blue) to 1.0 (warm, towards orange)
+    pub temperature: f32,
+    /// Mirror lightness so dark colors become light and vice versa
+    pub invert_lightness: bool,
+}
+
+impl Default for ColorAdjustment {
+    fn default() -> Self {
+        Self {
+            hue_shift: 0.0,
+            saturation: 1.0,
+            lightness: 1.0,
+            temperature: 0.0,
+            invert_lightness: false,
+        }
+    }
+}
+
+impl ColorAdjustment {
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn apply(&self, color: Color32) -> Color32 {
+        if self.is_identity() {
+            return color;
+        }
+
+        let [mut l, mut c, mut h] = to_oklch(color);
+        if self.invert_lightness {
+            l = 1.0 - l;
+        }
+        l = (l * self.lightness).clamp(0.0, 1.0);
+        c *= self.saturation;
+        h = (h + self.hue_shift).rem_euclid(360.0);
+        let shifted = from_oklch([l, c, h], color);
+
+        if self.temperature == 0.0 {
+            return shifted;
+        }
+        let [l, a, b] = to_oklab(shifted);
+        from_oklab(
+            [l, a + self.temperature * 0.01, b + self.temperature * 0.04],
+            shifted,
+        )
+    }
+}
+
+/// Runs `f` over every detected color of every cached file, writing results
+/// back in each color's original format. Colors `f` leaves unchanged are not
+/// rewritten.
+pub fn transform_file_cache(
+    file_cache: &HashMap<String, String>,
+    mut f: impl FnMut(Color32) -> Color32,
+) -> HashMap<String, String> {
+    file_cache
+        .iter()
+        .map(|(file, content)| {
+            let new_content = map_colors_in_content(content, |detected| {
+                let new_color = f(detected.value);
+                (new_color != detected.value).then(|| format_like(new_color, &detected.hex_text))
+            });
+            (file.clone(), new_content)
+        })
+        .collect()
+}
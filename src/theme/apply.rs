@@ -0,0 +1,286 @@
+use std::io::Read;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// How long the apply command may run before it is killed.
+pub const APPLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long output is still read after the command exits. Daemons restarted
+/// by the command inherit its stdout/stderr and may keep them open forever.
+const OUTPUT_GRACE: Duration = Duration::from_millis(200);
+
+#[derive(Clone, Debug)]
+pub struct ApplyOutput {
+    pub command: String,
+    pub dry_run: bool,
+    pub status: Option<i32>,
+    /// Killed after running longer than the timeout
+    pub timed_out: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Fills `{name}` and `{path}` in the configured command template.
+pub fn build_apply_command(template: &str, theme_name: &str, theme_path: &str) -> String {
+    template
+        .replace("{name}", &shell_quote(theme_name))
+        .replace("{path}", &shell_quote(theme_path))
+}
+
+/// Reads `pipe` on its own thread, sending chunks until end of file.
+fn read_chunks(mut pipe: impl Read + Send + 'static) -> Receiver<Vec<u8>> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        while let Ok(n) = pipe.read(&mut buf) {
+            if n == 0 || tx.send(buf[..n].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// Output received before `deadline` or the end of the pipe.
+fn collect_output(rx: &Receiver<Vec<u8>>, deadline: Instant) -> String {
+    let mut bytes = Vec::new();
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        match rx.recv_timeout(left) {
+            Ok(chunk) => bytes.extend(chunk),
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// Runs the apply command through `sh -c`, or only reports it when `dry_run`
+/// is set. Blocks until the command exits or `timeout` passes, so the app
+/// calls it through `spawn_apply_command`.
+pub fn run_apply_command(
+    template: &str,
+    theme_name: &str,
+    theme_path: &str,
+    dry_run: bool,
+    timeout: Duration,
+) -> Result<ApplyOutput, String> {
+    if template.trim().is_empty() {
+        return Err("No apply command configured".to_string());
+    }
+
+    let command = build_apply_command(template, theme_name, theme_path);
+    if dry_run {
+        eprintln!("[DEBUG] Dry run, not executing: {}", command);
+        return Ok(ApplyOutput {
+            command,
+            dry_run: true,
+            status: None,
+            timed_out: false,
+            stdout: String::new(),
+            stderr: String::new(),
+        });
+    }
+
+    eprintln!("[DEBUG] Running apply command: {}", command);
+    // Own process group, so a timeout also stops what the command started
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(&command)
+        .process_group(0)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run '{}': {}", command, e))?;
+    let stdout = child.stdout.take().map(read_chunks);
+    let stderr = child.stderr.take().map(read_chunks);
+
+    let started = Instant::now();
+    let mut timed_out = false;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status.code(),
+            Ok(None) if started.elapsed() >= timeout => {
+                eprintln!("[DEBUG] Apply command timed out, killing it");
+                // SAFETY: kill(2) with the negated group id of our own child
+                unsafe {
+                    libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+                }
+                let _ = child.wait();
+                timed_out = true;
+                break None;
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(20)),
+            Err(e) => return Err(format!("Failed to wait for '{}': {}", command, e)),
+        }
+    };
+
+    let deadline = Instant::now() + OUTPUT_GRACE;
+    Ok(ApplyOutput {
+        command,
+        dry_run: false,
+        status,
+        timed_out,
+        stdout: stdout
+            .map(|rx| collect_output(&rx, deadline))
+            .unwrap_or_default(),
+        stderr: stderr
+            .map(|rx| collect_output(&rx, deadline))
+            .unwrap_or_default(),
+    })
+}
+
+/// An apply command running on a worker thread.
+pub struct ApplyJob {
+    pub command: String,
+    pub result: Receiver<Result<ApplyOutput, String>>,
+}
+
+/// Runs `run_apply_command` on a worker thread so the window keeps drawing.
+pub fn spawn_apply_command(
+    template: &str,
+    theme_name: &str,
+    theme_path: &str,
+    dry_run: bool,
+) -> ApplyJob {
+    let command = build_apply_command(template, theme_name, theme_path);
+    let (tx, rx) = mpsc::channel();
+    let (template, theme_name, theme_path) = (
+        template.to_string(),
+        theme_name.to_string(),
+        theme_path.to_string(),
+    );
+    std::thread::spawn(move || {
+        let result = run_apply_command(&template, &theme_name, &theme_path, dry_run, APPLY_TIMEOUT);
+        let _ = tx.send(result);
+    });
+    ApplyJob {
+        command,
+        result: rx,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+
+    fn stub_script(name: &str, body: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("omarchy-apply-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn dry_run_only_reports_the_command() {
+        let output = run_apply_command(
+            "false {name} {path}",
+            "it's",
+            "/themes/it's",
+            true,
+            APPLY_TIMEOUT,
+        )
+        .unwrap();
+        assert!(output.dry_run);
+        assert_eq!(output.command, r"false 'it'\''s' '/themes/it'\''s'");
+        assert_eq!(output.status, None);
+        assert!(output.stdout.is_empty() && output.stderr.is_empty());
+    }
+
+    #[test]
+    fn stub_script_output_and_status() {
+        let script = stub_script(
+            "apply-stub.sh",
+            r#"echo "theme $1 at $2"; echo "warning: $1" >&2; exit 3"#,
+        );
+        let template = format!("{} {{name}} {{path}}", script.display());
+        let output =
+            run_apply_command(&template, "nord", "/themes/nord", false, APPLY_TIMEOUT).unwrap();
+        assert!(!output.dry_run);
+        assert!(!output.timed_out);
+        assert_eq!(output.status, Some(3));
+        assert_eq!(output.stdout, "theme nord at /themes/nord\n");
+        assert_eq!(output.stderr, "warning: nord\n");
+    }
+
+    #[test]
+    fn restarted_daemons_do_not_block() {
+        // Like omarchy-theme-set restarting waybar, the daemon keeps the pipes open
+        let script = stub_script("apply-daemon.sh", "sleep 10 &\necho applied");
+        let started = Instant::now();
+        let output = run_apply_command(
+            &script.to_string_lossy(),
+            "nord",
+            "/themes/nord",
+            false,
+            APPLY_TIMEOUT,
+        )
+        .unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(output.status, Some(0));
+        assert_eq!(output.stdout, "applied\n");
+    }
+
+    #[test]
+    fn hanging_command_times_out() {
+        let output = run_apply_command(
+            "echo started; exec sleep 10",
+            "nord",
+            "/themes/nord",
+            false,
+            Duration::from_millis(300),
+        )
+        .unwrap();
+        assert!(output.timed_out);
+        assert_eq!(output.status, None);
+        assert_eq!(output.stdout, "started\n");
+    }
+
+    #[test]
+    fn timeout_stops_background_children() {
+        let started = Instant::now();
+        let output = run_apply_command(
+            "sleep 30 & echo $!; sleep 30",
+            "nord",
+            "/themes/nord",
+            false,
+            Duration::from_millis(300),
+        )
+        .unwrap();
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(output.timed_out);
+
+        // Killed, the orphaned sleep is gone or a zombie waiting for init
+        let pid = output.stdout.trim();
+        let gone = (0..50).any(|_| {
+            std::thread::sleep(Duration::from_millis(20));
+            match std::fs::read_to_string(format!("/proc/{}/stat", pid)) {
+                Ok(stat) => stat.rsplit(") ").next().is_some_and(|s| s.starts_with('Z')),
+                Err(_) => true,
+            }
+        });
+        assert!(gone, "background sleep {} is still running", pid);
+    }
+
+    #[test]
+    fn worker_thread_sends_the_result() {
+        let job = spawn_apply_command("echo {name}", "nord", "/themes/nord", false);
+        assert_eq!(job.command, "echo 'nord'");
+        let output = job
+            .result
+            .recv_timeout(Duration::from_secs(5))
+            .unwrap()
+            .unwrap();
+        assert_eq!(output.stdout, "nord\n");
+    }
+}
@@ -0,0 +1,313 @@
+use std::fs::File;
+use std::path::{Component, Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use tar::{Archive, Builder, EntryType};
+
+use crate::theme::scanner::scan_theme_files;
+
+/// What an archive would unpack to, read before anything is written.
+#[derive(Clone, Debug)]
+pub struct ArchiveInfo {
+    /// Folder name inside the archive, or the archive's file stem when the
+    /// files are stored without one.
+    pub theme_name: String,
+    /// Files relative to the theme folder.
+    pub files: Vec<String>,
+    /// Whether entries are stored below a single `theme_name/` folder.
+    has_root_dir: bool,
+}
+
+/// Packs every file of the theme (including `backgrounds/` and other
+/// subfolders) below a `theme_name/` folder into a `.tar.gz`. An existing
+/// `dest` is left alone.
+pub fn export_theme_archive(theme_dir: &Path, theme_name: &str, dest: &Path) -> Result<(), String> {
+    if dest.exists() {
+        return Err(format!("{} already exists", dest.display()));
+    }
+    let file =
+        File::create(dest).map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+    let mut builder = Builder::new(GzEncoder::new(file, Compression::default()));
+
+    for name in scan_theme_files(&theme_dir.to_string_lossy()) {
+        builder
+            .append_path_with_name(theme_dir.join(&name), format!("{}/{}", theme_name, name))
+            .map_err(|e| format!("Failed to add {}: {}", name, e))?;
+    }
+    builder
+        .into_inner()
+        .and_then(|gz| gz.finish())
+        .map_err(|e| format!("Failed to write {}: {}", dest.display(), e))?;
+    Ok(())
+}
+
+/// Rejects absolute paths and anything that could leave the target folder.
+fn safe_relative_path(path: &Path) -> Result<PathBuf, String> {
+    let mut safe = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => safe.push(part),
+            Component::CurDir => {}
+            _ => return Err(format!("Unsafe path in archive: {}", path.display())),
+        }
+    }
+    Ok(safe)
+}
+
+fn open_archive(path: &Path) -> Result<Archive<GzDecoder<File>>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    Ok(Archive::new(GzDecoder::new(file)))
+}
+
+/// Lists the regular files of an archive, checking every entry. Links and
+/// device entries are refused.
+fn archive_files(path: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut archive = open_archive(path)?;
+    let mut files = Vec::new();
+    let entries = archive.entries().map_err(|e| e.to_string())?;
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Corrupt archive: {}", e))?;
+        let entry_path = entry.path().map_err(|e| e.to_string())?.into_owned();
+        let safe = safe_relative_path(&entry_path)?;
+        match entry.header().entry_type() {
+            EntryType::Regular | EntryType::Continuous => files.push(safe),
+            EntryType::Directory => {}
+            // pax/GNU headers carry metadata for the next entry
+            EntryType::XHeader | EntryType::XGlobalHeader | EntryType::GNULongName => {}
+            other => {
+                return Err(format!(
+                    "Unsupported entry {:?} in archive: {}",
+                    other,
+                    entry_path.display()
+                ))
+            }
+        }
+    }
+    if files.is_empty() {
+        return Err("Archive contains no files".to_string());
+    }
+    Ok(files)
+}
+
+pub fn inspect_archive(path: &Path) -> Result<ArchiveInfo, String> {
+    let files = archive_files(path)?;
+
+    let first_dir = |p: &PathBuf| {
+        let mut components = p.components();
+        let first = components.next()?;
+        components.next().map(|_| first.as_os_str().to_owned())
+    };
+    let root = first_dir(&files[0]);
+    let has_root_dir = root.is_some() && files.iter().all(|f| first_dir(f) == root);
+
+    let theme_name = match &root {
+        Some(root) if has_root_dir => root.to_string_lossy().into_owned(),
+        _ => {
+            let stem = path.file_name().unwrap_or_default().to_string_lossy();
+            let stem = stem.trim_end_matches(".gz").trim_end_matches(".tgz");
+            stem.trim_end_matches(".tar").to_string()
+        }
+    };
+    let files = files
+        .iter()
+        .map(|f| {
+            let relative = if has_root_dir {
+                f.components().skip(1).collect::<PathBuf>()
+            } else {
+                f.clone()
+            };
+            relative.to_string_lossy().replace('\\', "/")
+        })
+        .collect();
+
+    Ok(ArchiveInfo {
+        theme_name,
+        files,
+        has_root_dir,
+    })
+}
+
+/// Unpacks the files of a checked archive into `dest`, which must not exist.
+/// A failed unpack removes `dest` again.
+pub fn unpack_archive(path: &Path, info: &ArchiveInfo, dest: &Path) -> Result<(), String> {
+    if dest.exists() {
+        return Err(format!("{} already exists", dest.display()));
+    }
+    unpack_entries(path, info, dest).inspect_err(|_| {
+        let _ = std::fs::remove_dir_all(dest);
+    })
+}
+
+fn unpack_entries(path: &Path, info: &ArchiveInfo, dest: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dest).map_err(|e| e.to_string())?;
+
+    let mut archive = open_archive(path)?;
+    let entries = archive.entries().map_err(|e| e.to_string())?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("Corrupt archive: {}", e))?;
+        if !matches!(
+            entry.header().entry_type(),
+            EntryType::Regular | EntryType::Continuous
+        ) {
+            continue;
+        }
+        let safe = safe_relative_path(&entry.path().map_err(|e| e.to_string())?)?;
+        let relative: PathBuf = if info.has_root_dir {
+            safe.components().skip(1).collect()
+        } else {
+            safe
+        };
+        let target = dest.join(&relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let mut out = File::create(&target)
+            .map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+        std::io::copy(&mut entry, &mut out)
+            .map_err(|e| format!("Failed to unpack {}: {}", relative.display(), e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tar::Header;
+
+    /// Empty scratch folder unique to this test process.
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("omarchy-archive-test-{}", std::process::id()))
+            .join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Entry name, type and content (the target for links).
+    type RawEntry<'a> = (&'a str, EntryType, &'a str);
+
+    /// Writes a `.tar.gz` with the given raw entry names, bypassing the
+    /// path checks of `tar::Header::set_path`.
+    fn raw_archive(path: &Path, entries: &[RawEntry]) {
+        let file = File::create(path).unwrap();
+        let mut builder = Builder::new(GzEncoder::new(file, Compression::default()));
+        for (name, kind, content) in entries {
+            let mut header = Header::new_gnu();
+            header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_entry_type(*kind);
+            header.set_mode(0o644);
+            if *kind == EntryType::Symlink {
+                header.set_link_name(content).unwrap();
+                header.set_size(0);
+                header.set_cksum();
+                builder.append(&header, std::io::empty()).unwrap();
+            } else {
+                header.set_size(content.len() as u64);
+                header.set_cksum();
+                builder.append(&header, content.as_bytes()).unwrap();
+            }
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn export_then_import_round_trips() {
+        let root = scratch("round-trip");
+        let theme = root.join("nord");
+        std::fs::create_dir_all(theme.join("backgrounds")).unwrap();
+        std::fs::write(theme.join("colors.toml"), "accent = \"#89b4fa\"\n").unwrap();
+        std::fs::write(theme.join("backgrounds/1.png"), [0x89, b'P', b'N', b'G']).unwrap();
+        let archive = root.join("nord.tar.gz");
+
+        export_theme_archive(&theme, "nord", &archive).unwrap();
+        let info = inspect_archive(&archive).unwrap();
+        assert_eq!(info.theme_name, "nord");
+        let mut files = info.files.clone();
+        files.sort();
+        assert_eq!(files, ["backgrounds/1.png", "colors.toml"]);
+
+        let dest = root.join("nord-copy");
+        unpack_archive(&archive, &info, &dest).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dest.join("colors.toml")).unwrap(),
+            "accent = \"#89b4fa\"\n"
+        );
+        assert_eq!(
+            std::fs::read(dest.join("backgrounds/1.png")).unwrap(),
+            [0x89, b'P', b'N', b'G']
+        );
+    }
+
+    #[test]
+    fn export_keeps_an_existing_file() {
+        let root = scratch("export-exists");
+        let theme = root.join("nord");
+        std::fs::create_dir_all(&theme).unwrap();
+        std::fs::write(theme.join("colors.toml"), "").unwrap();
+        let archive = root.join("nord.tar.gz");
+        std::fs::write(&archive, "keep").unwrap();
+
+        assert!(export_theme_archive(&theme, "nord", &archive).is_err());
+        assert_eq!(std::fs::read_to_string(&archive).unwrap(), "keep");
+    }
+
+    #[test]
+    fn unsafe_paths_are_rejected() {
+        assert!(safe_relative_path(Path::new("nord/../../.bashrc")).is_err());
+        assert!(safe_relative_path(Path::new("/etc/passwd")).is_err());
+        assert_eq!(
+            safe_relative_path(Path::new("./nord/colors.toml")).unwrap(),
+            PathBuf::from("nord/colors.toml")
+        );
+    }
+
+    #[test]
+    fn archives_with_unsafe_entries_are_rejected() {
+        let root = scratch("unsafe");
+        let cases: [(&str, &[RawEntry]); 3] = [
+            ("dotdot", &[("nord/../../evil", EntryType::Regular, "x")]),
+            ("absolute", &[("/tmp/evil", EntryType::Regular, "x")]),
+            (
+                "symlink",
+                &[
+                    ("nord/colors.toml", EntryType::Regular, ""),
+                    ("nord/link", EntryType::Symlink, "/etc/passwd"),
+                ],
+            ),
+        ];
+        for (name, entries) in cases {
+            let archive = root.join(format!("{}.tar.gz", name));
+            raw_archive(&archive, entries);
+            let err = archive_files(&archive).unwrap_err();
+            assert!(
+                err.starts_with("Unsafe path") || err.starts_with("Unsupported entry"),
+                "{}: {}",
+                name,
+                err
+            );
+            assert!(inspect_archive(&archive).is_err(), "{} was accepted", name);
+        }
+    }
+
+    #[test]
+    fn failed_unpack_leaves_no_folder() {
+        let root = scratch("unpack-fails");
+        let archive = root.join("nord.tar.gz");
+        // `colors` is a file, so `colors/extra.toml` can't be created below it
+        raw_archive(
+            &archive,
+            &[
+                ("nord/colors", EntryType::Regular, "x"),
+                ("nord/colors/extra.toml", EntryType::Regular, "y"),
+            ],
+        );
+        let info = inspect_archive(&archive).unwrap();
+        let dest = root.join("nord");
+
+        assert!(unpack_archive(&archive, &info, &dest).is_err());
+        assert!(!dest.exists());
+    }
+}
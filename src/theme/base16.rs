@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+
+use egui::Color32;
+use serde_yaml::Value;
+
+use crate::theme::semantic::{remap_by_role, ColorRole, RemapMode, ThemeColors};
+use crate::utils::color::{opaque, parse_hex_color};
+use crate::utils::colorspace::mix;
+
+/// Theme files generated for a scheme. `{{base00}}`..`{{base17}}` and
+/// `{{ansi0}}`..`{{ansi15}}` are replaced by hex digits without `#`.
+const TEMPLATES: &[(&str, &str)] = &[
+    ("alacritty.toml", include_str!("templates/alacritty.toml")),
+    ("kitty.conf", include_str!("templates/kitty.conf")),
+    ("ghostty.conf", include_str!("templates/ghostty.conf")),
+    ("hyprland.conf", include_str!("templates/hyprland.conf")),
+    ("hyprlock.conf", include_str!("templates/hyprlock.conf")),
+    ("waybar.css", include_str!("templates/waybar.css")),
+    ("walker.css", include_str!("templates/walker.css")),
+    ("swayosd.css", include_str!("templates/swayosd.css")),
+    ("mako.ini", include_str!("templates/mako.ini")),
+    ("btop.theme", include_str!("templates/btop.theme")),
+];
+
+/// A base16 (16 slots) or base24 (24 slots) color scheme.
+#[derive(Clone, Debug)]
+pub struct Base16Scheme {
+    pub name: String,
+    pub author: String,
+    pub slots: Vec<Color32>,
+}
+
+pub fn slot_name(index: usize) -> String {
+    format!("base{:02X}", index)
+}
+
+/// Scheme slot conventionally used for a role.
+fn role_slot(role: ColorRole, base24: bool) -> usize {
+    match role {
+        ColorRole::Background => 0x00,
+        ColorRole::Foreground | ColorRole::Cursor => 0x05,
+        ColorRole::Selection => 0x02,
+        ColorRole::Accent | ColorRole::ActiveBorder => 0x0D,
+        ColorRole::InactiveBorder => 0x03,
+        ColorRole::Ansi(n) => ansi_slot(n, base24),
+    }
+}
+
+/// Scheme slot of ANSI color `n`, following the base16 shell templates.
+/// base24 schemes have their own bright colors in base12..base17.
+fn ansi_slot(n: u8, base24: bool) -> usize {
+    const NORMAL: [usize; 8] = [0x00, 0x08, 0x0B, 0x0A, 0x0D, 0x0E, 0x0C, 0x05];
+    const BRIGHT24: [usize; 8] = [0x03, 0x12, 0x14, 0x13, 0x16, 0x17, 0x15, 0x07];
+    match n {
+        0..=7 => NORMAL[n as usize],
+        8 if !base24 => 0x03,
+        15 if !base24 => 0x07,
+        _ if base24 => BRIGHT24[(n - 8) as usize],
+        _ => NORMAL[(n - 8) as usize],
+    }
+}
+
+fn yaml_str<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(Value::as_str)
+}
+
+impl Base16Scheme {
+    /// Parses both the classic layout (`scheme:` with top-level `base00:`)
+    /// and the tinted-theming layout (`name:` with a `palette:` map).
+    pub fn parse(yaml: &str) -> Result<Self, String> {
+        let doc: Value =
+            serde_yaml::from_str(yaml).map_err(|e| format!("Invalid scheme YAML: {}", e))?;
+        let palette = doc.get("palette").unwrap_or(&doc);
+
+        let mut slots = Vec::new();
+        for i in 0..24 {
+            let name = slot_name(i);
+            let value = palette
+                .get(&name)
+                .or_else(|| palette.get(name.to_lowercase()));
+            let Some(value) = value else { break };
+            let hex = value
+                .as_str()
+                .ok_or_else(|| format!("{} is not a string", name))?;
+            let color = parse_hex_color(hex.trim())
+                .filter(|_| hex.trim().trim_start_matches('#').len() == 6)
+                .ok_or_else(|| format!("{} is not a hex color: {}", name, hex))?;
+            slots.push(color);
+        }
+        if slots.len() != 16 && slots.len() != 24 {
+            return Err(format!(
+                "Expected base00..base0F or base00..base17, found {} colors",
+                slots.len()
+            ));
+        }
+
+        let name = yaml_str(&doc, "name")
+            .or_else(|| yaml_str(&doc, "scheme"))
+            .unwrap_or("base16")
+            .to_string();
+        let author = yaml_str(&doc, "author").unwrap_or_default().to_string();
+        Ok(Self {
+            name,
+            author,
+            slots,
+        })
+    }
+
+    /// Derives a base16 scheme from a theme's semantic colors. Slots no
+    /// config key maps to are blended from the neighbouring ones.
+    pub fn from_theme(name: &str, colors: &ThemeColors) -> Self {
+        let hints = ["alacritty", "kitty", "ghostty"];
+        let role = |role| colors.role(role, &hints);
+        let bg = role(ColorRole::Background).unwrap_or(Color32::from_rgb(0x1e, 0x1e, 0x2e));
+        let fg = role(ColorRole::Foreground).unwrap_or(Color32::from_rgb(0xcd, 0xd6, 0xf4));
+        let ansi = |n: u8, fallback: Color32| role(ColorRole::Ansi(n)).unwrap_or(fallback);
+
+        let red = ansi(1, Color32::from_rgb(0xf3, 0x8b, 0xa8));
+        let yellow = ansi(3, Color32::from_rgb(0xf9, 0xe2, 0xaf));
+        let bright_white = ansi(15, mix(fg, Color32::WHITE, 0.3));
+        let slots = vec![
+            bg,
+            mix(bg, fg, 0.08),
+            role(ColorRole::Selection).unwrap_or(mix(bg, fg, 0.18)),
+            ansi(8, mix(bg, fg, 0.35)),
+            mix(bg, fg, 0.7),
+            fg,
+            mix(fg, bright_white, 0.5),
+            bright_white,
+            red,
+            mix(red, yellow, 0.5),
+            yellow,
+            ansi(2, Color32::from_rgb(0xa6, 0xe3, 0xa1)),
+            ansi(6, Color32::from_rgb(0x94, 0xe2, 0xd5)),
+            ansi(4, Color32::from_rgb(0x89, 0xb4, 0xfa)),
+            ansi(5, Color32::from_rgb(0xf5, 0xc2, 0xe7)),
+            mix(red, bg, 0.35),
+        ];
+        Self {
+            name: name.to_string(),
+            author: String::new(),
+            slots: slots.into_iter().map(opaque).collect(),
+        }
+    }
+
+    /// Classic base16 YAML, readable by base16 builders.
+    pub fn to_yaml(&self) -> String {
+        let mut out = format!("scheme: \"{}\"\nauthor: \"{}\"\n", self.name, self.author);
+        for (i, color) in self.slots.iter().enumerate() {
+            out.push_str(&format!("{}: \"{}\"\n", slot_name(i), Self::hex(*color)));
+        }
+        out
+    }
+
+    pub fn load(path: &std::path::Path) -> Result<Self, String> {
+        let yaml = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::parse(&yaml)
+    }
+
+    pub fn is_base24(&self) -> bool {
+        self.slots.len() == 24
+    }
+
+    /// A theme folder name derived from the scheme name.
+    pub fn theme_name(&self) -> String {
+        let slug: String = self
+            .name
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        let slug = slug
+            .split('-')
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+        if slug.is_empty() {
+            "base16".to_string()
+        } else {
+            slug
+        }
+    }
+
+    fn hex(color: Color32) -> String {
+        format!("{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+    }
+
+    fn render(&self, template: &str) -> String {
+        let mut out = template.to_string();
+        for n in 0..16u8 {
+            let color = self.slots[ansi_slot(n, self.is_base24())];
+            out = out.replace(&format!("{{{{ansi{}}}}}", n), &Self::hex(color));
+        }
+        for (i, color) in self.slots.iter().enumerate() {
+            out = out.replace(&format!("{{{{{}}}}}", slot_name(i)), &Self::hex(*color));
+        }
+        out
+    }
+
+    /// The files of a new theme built from the bundled templates.
+    pub fn generate_theme(&self) -> HashMap<String, String> {
+        TEMPLATES
+            .iter()
+            .map(|(file, template)| (file.to_string(), self.render(template)))
+            .collect()
+    }
+
+    /// Recolors `file_cache` with this scheme, keeping each color's format
+    /// and alpha.
+    pub fn remap_cache(
+        &self,
+        file_cache: &HashMap<String, String>,
+        mode: RemapMode,
+    ) -> HashMap<String, String> {
+        let base24 = self.is_base24();
+        remap_by_role(file_cache, mode, |role| {
+            Some(self.slots[role_slot(role, base24)])
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Classic layout with `count` slots, colors `#0000NN` for slot NN.
+    fn classic(count: usize) -> String {
+        let mut yaml = "scheme: \"Test Scheme\"\nauthor: \"tester\"\n".to_string();
+        for i in 0..count {
+            yaml.push_str(&format!("{}: \"0000{:02x}\"\n", slot_name(i), i));
+        }
+        yaml
+    }
+
+    #[test]
+    fn parses_classic_base16() {
+        let scheme = Base16Scheme::parse(&classic(16)).unwrap();
+        assert_eq!(scheme.name, "Test Scheme");
+        assert_eq!(scheme.author, "tester");
+        assert!(!scheme.is_base24());
+        assert_eq!(scheme.slots[0x0D], Color32::from_rgb(0, 0, 0x0D));
+        assert_eq!(scheme.theme_name(), "test-scheme");
+    }
+
+    #[test]
+    fn parses_tinted_base24_with_hash_prefixes() {
+        let mut yaml = "name: Tinted\npalette:\n".to_string();
+        for i in 0..24 {
+            yaml.push_str(&format!(
+                "  {}: \"#0000{:02x}\"\n",
+                slot_name(i).to_lowercase(),
+                i
+            ));
+        }
+        let scheme = Base16Scheme::parse(&yaml).unwrap();
+        assert_eq!(scheme.name, "Tinted");
+        assert!(scheme.is_base24());
+        assert_eq!(scheme.slots[0x17], Color32::from_rgb(0, 0, 0x17));
+    }
+
+    #[test]
+    fn parses_json_schemes() {
+        let slots: Vec<String> = (0..16)
+            .map(|i| format!("\"{}\": \"#0000{:02x}\"", slot_name(i), i))
+            .collect();
+        let json = format!("{{\"scheme\": \"Json\", {}}}", slots.join(", "));
+        let scheme = Base16Scheme::parse(&json).unwrap();
+        assert_eq!(scheme.name, "Json");
+        assert_eq!(scheme.slots.len(), 16);
+    }
+
+    #[test]
+    fn rejects_missing_or_malformed_slots() {
+        assert!(Base16Scheme::parse(&classic(15)).is_err());
+        assert!(Base16Scheme::parse(&classic(20)).is_err());
+        let gap = classic(16).replace("base03: \"000003\"\n", "");
+        assert!(Base16Scheme::parse(&gap).is_err());
+        let short = classic(16).replace("\"00000d\"", "\"#00d\"");
+        assert!(Base16Scheme::parse(&short)
+            .unwrap_err()
+            .starts_with("base0D is not a hex color"));
+        let number = classic(16).replace("\"000005\"", "5");
+        assert!(Base16Scheme::parse(&number).is_err());
+        assert!(Base16Scheme::parse("base00: [").is_err());
+    }
+
+    #[test]
+    fn bright_ansi_colors_follow_the_scheme_kind() {
+        let base16 = Base16Scheme::parse(&classic(16)).unwrap();
+        let base24 = Base16Scheme::parse(&classic(24)).unwrap();
+        assert_eq!(
+            base16.render("{{ansi1}} {{ansi9}} {{ansi8}}"),
+            "000008 000008 000003"
+        );
+        assert_eq!(
+            base24.render("{{ansi1}} {{ansi9}} {{ansi8}}"),
+            "000008 000012 000003"
+        );
+        assert_eq!(base24.render("{{base17}}"), "000017");
+    }
+}
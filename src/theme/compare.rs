@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use egui::Color32;
+
+use crate::theme::scanner::{is_text_file, read_theme_files, scan_theme_files};
+use crate::theme::semantic::{keyed_colors, KeyedColor, ThemeColors};
+use crate::utils::colorspace::delta_e;
+
+/// Palette colors closer than this count as the same color in both themes.
+const SAME_COLOR_DELTA_E: f32 = 2.0;
+
+/// A color key whose value differs, or that exists on one side only.
+#[derive(Clone, Debug)]
+pub struct KeyDiff {
+    pub key: String,
+    pub left: Option<Color32>,
+    pub right: Option<Color32>,
+}
+
+/// A file present in both themes.
+#[derive(Clone, Debug)]
+pub struct FileComparison {
+    pub file: String,
+    pub identical: bool,
+    pub key_diffs: Vec<KeyDiff>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ThemeComparison {
+    pub left: String,
+    pub right: String,
+    pub only_left: Vec<String>,
+    pub only_right: Vec<String>,
+    pub files: Vec<FileComparison>,
+    /// Palette colors with no close match in the other theme
+    pub palette_only_left: Vec<Color32>,
+    pub palette_only_right: Vec<Color32>,
+}
+
+/// The keyed colors of a file with keys made unique: further colors on the
+/// same key (e.g. gradient stops) get `#2`, `#3`, … appended.
+pub fn numbered_keys(file: &str, content: &str) -> Vec<(String, KeyedColor)> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    keyed_colors(file, content)
+        .into_iter()
+        .map(|c| {
+            let count = seen.entry(c.key.clone()).or_insert(0);
+            *count += 1;
+            let key = if *count == 1 {
+                c.key.clone()
+            } else {
+                format!("{}#{}", c.key, count)
+            };
+            (key, c)
+        })
+        .collect()
+}
+
+pub fn colors_by_key(file: &str, content: &str) -> HashMap<String, Color32> {
+    numbered_keys(file, content)
+        .into_iter()
+        .map(|(key, c)| (key, c.color.value))
+        .collect()
+}
+
+fn compare_keys(file: &str, left: &str, right: &str) -> Vec<KeyDiff> {
+    let left = colors_by_key(file, left);
+    let right = colors_by_key(file, right);
+    let mut keys: Vec<&String> = left.keys().chain(right.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    keys.into_iter()
+        .filter_map(|key| {
+            let (l, r) = (left.get(key).copied(), right.get(key).copied());
+            (l != r).then(|| KeyDiff {
+                key: key.clone(),
+                left: l,
+                right: r,
+            })
+        })
+        .collect()
+}
+
+fn palette_missing_from(colors: &[Color32], other: &[Color32]) -> Vec<Color32> {
+    colors
+        .iter()
+        .filter(|c| other.iter().all(|o| delta_e(**c, *o) >= SAME_COLOR_DELTA_E))
+        .copied()
+        .collect()
+}
+
+/// Compares two themes. `left_cache` lets the loaded theme be compared
+/// with its unsaved edits.
+pub fn compare_themes(
+    left: (&str, &Path, &HashMap<String, String>),
+    right: (&str, &Path),
+) -> ThemeComparison {
+    let (left_name, left_dir, left_cache) = left;
+    let (right_name, right_dir) = right;
+
+    let mut left_files = scan_theme_files(&left_dir.to_string_lossy());
+    let right_files = scan_theme_files(&right_dir.to_string_lossy());
+    for file in left_cache.keys() {
+        if !left_files.contains(file) {
+            left_files.push(file.clone());
+        }
+    }
+    let (right_cache, _) = read_theme_files(&right_dir.to_string_lossy(), &right_files);
+
+    let mut only_left: Vec<String> = left_files
+        .iter()
+        .filter(|f| !right_files.contains(f))
+        .cloned()
+        .collect();
+    let mut only_right: Vec<String> = right_files
+        .iter()
+        .filter(|f| !left_files.contains(f))
+        .cloned()
+        .collect();
+    only_left.sort();
+    only_right.sort();
+
+    let mut common: Vec<&String> = left_files
+        .iter()
+        .filter(|f| right_files.contains(f))
+        .collect();
+    common.sort();
+    let files = common
+        .into_iter()
+        .map(|file| match (left_cache.get(file), right_cache.get(file)) {
+            (Some(l), Some(r)) => FileComparison {
+                file: file.clone(),
+                identical: l == r,
+                key_diffs: compare_keys(file, l, r),
+            },
+            // Binary files, or text on one side only: compare bytes
+            _ => {
+                let read = |dir: &Path| std::fs::read(dir.join(file)).ok();
+                let text_changed =
+                    is_text_file(&left_dir.join(file)) != is_text_file(&right_dir.join(file));
+                FileComparison {
+                    file: file.clone(),
+                    identical: !text_changed && read(left_dir) == read(right_dir),
+                    key_diffs: vec![],
+                }
+            }
+        })
+        .collect();
+
+    let palette = |cache: &HashMap<String, String>| -> Vec<Color32> {
+        ThemeColors::from_cache(cache)
+            .palette()
+            .into_iter()
+            .map(|e| e.color)
+            .collect()
+    };
+    let (left_palette, right_palette) = (palette(left_cache), palette(&right_cache));
+
+    ThemeComparison {
+        left: left_name.to_string(),
+        right: right_name.to_string(),
+        only_left,
+        only_right,
+        files,
+        palette_only_left: palette_missing_from(&left_palette, &right_palette),
+        palette_only_right: palette_missing_from(&right_palette, &left_palette),
+    }
+}
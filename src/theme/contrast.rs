@@ -0,0 +1,205 @@
+use egui::Color32;
+
+use crate::theme::semantic::{ColorRole, KeyedColor, ThemeColors};
+use crate::utils::color::{apca_contrast, contrast_ratio};
+
+/// A text/background pair in one app config, matched by file name and key.
+struct ContrastPair {
+    file_hint: &'static str,
+    label: &'static str,
+    foreground: &'static [&'static str],
+    background: &'static [&'static str],
+}
+
+const PAIRS: &[ContrastPair] = &[
+    ContrastPair {
+        file_hint: "alacritty",
+        label: "Terminal text",
+        foreground: &["foreground"],
+        background: &["background"],
+    },
+    ContrastPair {
+        file_hint: "kitty",
+        label: "Terminal text",
+        foreground: &["foreground"],
+        background: &["background"],
+    },
+    ContrastPair {
+        file_hint: "ghostty",
+        label: "Terminal text",
+        foreground: &["foreground"],
+        background: &["background"],
+    },
+    ContrastPair {
+        file_hint: "waybar",
+        label: "Bar text",
+        foreground: &["foreground", "fg"],
+        background: &["background", "bg"],
+    },
+    ContrastPair {
+        file_hint: "walker",
+        label: "Launcher text",
+        foreground: &["text", "foreground"],
+        background: &["base", "background"],
+    },
+    ContrastPair {
+        file_hint: "walker",
+        label: "Launcher selection",
+        foreground: &["selected-text"],
+        background: &["base", "background"],
+    },
+    ContrastPair {
+        file_hint: "swayosd",
+        label: "OSD label",
+        foreground: &["label", "foreground"],
+        background: &["background-color", "background"],
+    },
+    ContrastPair {
+        file_hint: "mako",
+        label: "Notification text",
+        foreground: &["text-color"],
+        background: &["background-color"],
+    },
+    ContrastPair {
+        file_hint: "btop",
+        label: "btop text",
+        foreground: &["main_fg"],
+        background: &["main_bg"],
+    },
+    ContrastPair {
+        file_hint: "hyprlock",
+        label: "Lock screen input",
+        foreground: &["font_color"],
+        background: &["inner_color"],
+    },
+    ContrastPair {
+        file_hint: "neovim",
+        label: "Editor text",
+        foreground: &["fg", "foreground"],
+        background: &["bg", "background"],
+    },
+];
+
+const TERMINAL_FILES: &[&str] = &["alacritty", "kitty", "ghostty"];
+
+const ANSI_LABELS: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright black",
+    "bright red",
+    "bright green",
+    "bright yellow",
+    "bright blue",
+    "bright magenta",
+    "bright cyan",
+    "bright white",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ContrastLevel {
+    Fail,
+    AaLarge,
+    Aa,
+    Aaa,
+}
+
+impl ContrastLevel {
+    pub fn from_ratio(ratio: f32) -> Self {
+        if ratio >= 7.0 {
+            ContrastLevel::Aaa
+        } else if ratio >= 4.5 {
+            ContrastLevel::Aa
+        } else if ratio >= 3.0 {
+            ContrastLevel::AaLarge
+        } else {
+            ContrastLevel::Fail
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ContrastLevel::Aaa => "AAA",
+            ContrastLevel::Aa => "AA",
+            ContrastLevel::AaLarge => "AA Large",
+            ContrastLevel::Fail => "Fail",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ContrastResult {
+    pub file: String,
+    pub label: String,
+    /// File and key the text color was read from
+    pub foreground_at: (String, String),
+    pub foreground: Color32,
+    pub background: Color32,
+    pub ratio: f32,
+    /// APCA Lc, signed
+    pub apca: f32,
+    /// Body text pairs must pass; ANSI colors on the terminal background are
+    /// informational since palettes often use low contrast on purpose.
+    pub required: bool,
+}
+
+impl ContrastResult {
+    fn new(label: String, fg: &KeyedColor, bg: &KeyedColor, required: bool) -> Self {
+        let (foreground, background) = (fg.color.value, bg.color.value);
+        Self {
+            file: bg.file.clone(),
+            label,
+            foreground_at: (fg.file.clone(), fg.key.clone()),
+            foreground,
+            background,
+            ratio: contrast_ratio(foreground, background),
+            apca: apca_contrast(foreground, background),
+            required,
+        }
+    }
+
+    pub fn level(&self) -> ContrastLevel {
+        ContrastLevel::from_ratio(self.ratio)
+    }
+}
+
+/// Checks the known foreground/background pairs of every app config in the
+/// theme, plus the ANSI palette against the terminal background.
+pub fn check_theme_contrast(colors: &ThemeColors) -> Vec<ContrastResult> {
+    let mut results = Vec::new();
+
+    for pair in PAIRS {
+        let fg = colors.find_keyed(pair.file_hint, pair.foreground);
+        let bg = colors.find_keyed(pair.file_hint, pair.background);
+        if let (Some(fg), Some(bg)) = (fg, bg) {
+            results.push(ContrastResult::new(pair.label.to_string(), fg, bg, true));
+        }
+    }
+
+    let terminal_bg = TERMINAL_FILES
+        .iter()
+        .find_map(|hint| colors.find_keyed(hint, &["background"]));
+    if let Some(bg) = terminal_bg {
+        for (i, label) in ANSI_LABELS.iter().enumerate() {
+            // Black is meant to blend into the background
+            if i == 0 {
+                continue;
+            }
+            if let Some(fg) = colors.role_keyed(ColorRole::Ansi(i as u8), &[bg.file.as_str()]) {
+                results.push(ContrastResult::new(
+                    format!("ANSI {}", label),
+                    fg,
+                    bg,
+                    false,
+                ));
+            }
+        }
+    }
+
+    results
+}
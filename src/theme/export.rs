@@ -0,0 +1,160 @@
+use std::collections::{BTreeMap, HashMap};
+
+use egui::Color32;
+
+use crate::theme::base16::Base16Scheme;
+use crate::theme::semantic::{key_name, ColorRole, ThemeColors};
+
+/// Palette file formats for other tools.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaletteFormat {
+    Gpl,
+    Base16Yaml,
+    Json,
+    Css,
+    Xresources,
+}
+
+impl PaletteFormat {
+    pub const ALL: [PaletteFormat; 5] = [
+        PaletteFormat::Gpl,
+        PaletteFormat::Base16Yaml,
+        PaletteFormat::Json,
+        PaletteFormat::Css,
+        PaletteFormat::Xresources,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PaletteFormat::Gpl => "GIMP/Inkscape palette (.gpl)",
+            PaletteFormat::Base16Yaml => "base16 scheme (.yaml)",
+            PaletteFormat::Json => "JSON keys to colors (.json)",
+            PaletteFormat::Css => "CSS variables (.css)",
+            PaletteFormat::Xresources => "Xresources",
+        }
+    }
+
+    /// File name suffix, including the dot where there is one.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PaletteFormat::Gpl => ".gpl",
+            PaletteFormat::Base16Yaml => ".yaml",
+            PaletteFormat::Json => ".json",
+            PaletteFormat::Css => ".css",
+            PaletteFormat::Xresources => ".Xresources",
+        }
+    }
+}
+
+fn hex(color: Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    if a == 255 {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    } else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    }
+}
+
+/// A CSS/X identifier made of lowercase letters, digits and dashes.
+fn ident(text: &str) -> String {
+    let raw: String = text
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    raw.split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// One unique name per palette entry, taken from the first key using it.
+fn palette_names(colors: &ThemeColors) -> Vec<(String, Color32)> {
+    let mut used: HashMap<String, usize> = HashMap::new();
+    colors
+        .palette()
+        .into_iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let key = entry.keys[0].rsplit(": ").next().unwrap_or_default();
+            let mut name = ident(&key_name(key));
+            if name.is_empty() {
+                name = format!("color-{}", i);
+            }
+            let count = used.entry(name.clone()).or_insert(0);
+            *count += 1;
+            if *count > 1 {
+                name = format!("{}-{}", name, count);
+            }
+            (name, entry.color)
+        })
+        .collect()
+}
+
+fn to_gpl(theme_name: &str, colors: &ThemeColors) -> String {
+    let mut out = format!("GIMP Palette\nName: {}\nColumns: 8\n#\n", theme_name);
+    for (name, color) in palette_names(colors) {
+        out.push_str(&format!(
+            "{:>3} {:>3} {:>3}\t{}\n",
+            color.r(),
+            color.g(),
+            color.b(),
+            name
+        ));
+    }
+    out
+}
+
+/// `{ "file": { "key": "#rrggbb" } }`. Further colors on the same key get
+/// `.1`, `.2`, … appended.
+fn to_json(colors: &ThemeColors) -> String {
+    let mut files: BTreeMap<&str, BTreeMap<String, String>> = BTreeMap::new();
+    for c in &colors.colors {
+        let keys = files.entry(&c.file).or_default();
+        let mut key = c.key.clone();
+        let mut n = 1;
+        while keys.contains_key(&key) {
+            key = format!("{}.{}", c.key, n);
+            n += 1;
+        }
+        keys.insert(key, hex(c.color.value));
+    }
+    serde_json::to_string_pretty(&files).unwrap_or_default() + "\n"
+}
+
+fn to_css(theme_name: &str, colors: &ThemeColors) -> String {
+    let mut out = format!("/* {} */\n:root {{\n", theme_name);
+    for (name, color) in palette_names(colors) {
+        out.push_str(&format!("  --{}: {};\n", name, hex(color)));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn to_xresources(theme_name: &str, colors: &ThemeColors) -> String {
+    let hints = ["alacritty", "kitty", "ghostty"];
+    let mut out = format!("! {}\n", theme_name);
+    let mut push = |resource: &str, role| {
+        if let Some(color) = colors.role(role, &hints) {
+            out.push_str(&format!("*.{}: {}\n", resource, hex(color)));
+        }
+    };
+    push("background", ColorRole::Background);
+    push("foreground", ColorRole::Foreground);
+    push("cursorColor", ColorRole::Cursor);
+    for n in 0..16 {
+        push(&format!("color{}", n), ColorRole::Ansi(n));
+    }
+    out
+}
+
+/// Renders the theme's colors in `format`.
+pub fn export_palette(theme_name: &str, colors: &ThemeColors, format: PaletteFormat) -> String {
+    match format {
+        PaletteFormat::Gpl => to_gpl(theme_name, colors),
+        PaletteFormat::Base16Yaml => Base16Scheme::from_theme(theme_name, colors).to_yaml(),
+        PaletteFormat::Json => to_json(colors),
+        PaletteFormat::Css => to_css(theme_name, colors),
+        PaletteFormat::Xresources => to_xresources(theme_name, colors),
+    }
+}
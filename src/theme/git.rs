@@ -0,0 +1,500 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+
+use git2::build::{CheckoutBuilder, RepoBuilder};
+use git2::{DiffFormat, FetchOptions, IndexAddOption, Oid, RemoteCallbacks, Repository, Signature};
+
+/// Folder name for a theme cloned from `url`: the last path segment without
+/// `.git`, e.g. `omarchy-nord-theme` for `https://host/user/omarchy-nord-theme.git`.
+pub fn theme_name_from_url(url: &str) -> String {
+    let trimmed = url.trim().trim_end_matches('/');
+    let last = trimmed.rsplit(['/', ':']).next().unwrap_or_default();
+    last.trim_end_matches(".git").to_string()
+}
+
+/// Objects received so far by a clone or fetch.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TransferProgress {
+    pub received_objects: usize,
+    pub total_objects: usize,
+    pub received_bytes: usize,
+}
+
+/// Progress and cancellation shared between a clone or fetch on a worker
+/// thread and the UI.
+#[derive(Default)]
+pub struct Transfer {
+    progress: Mutex<TransferProgress>,
+    cancelled: AtomicBool,
+}
+
+impl Transfer {
+    pub fn progress(&self) -> TransferProgress {
+        self.progress.lock().map(|p| *p).unwrap_or_default()
+    }
+
+    /// Stops the transfer at the next progress update.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn fetch_options(&self) -> FetchOptions<'_> {
+        let mut callbacks = RemoteCallbacks::new();
+        callbacks.transfer_progress(|p| {
+            if let Ok(mut progress) = self.progress.lock() {
+                *progress = TransferProgress {
+                    received_objects: p.received_objects(),
+                    total_objects: p.total_objects(),
+                    received_bytes: p.received_bytes(),
+                };
+            }
+            !self.is_cancelled()
+        });
+        let mut options = FetchOptions::new();
+        options.remote_callbacks(callbacks);
+        options
+    }
+
+    fn error(&self, context: &str, e: git2::Error) -> String {
+        if self.is_cancelled() {
+            "Cancelled".to_string()
+        } else {
+            format!("{}: {}", context, e.message())
+        }
+    }
+}
+
+/// Clones a theme from a remote URL, a `file://` URL or a local path. A
+/// failed or cancelled clone leaves no folder behind.
+pub fn clone_theme(url: &str, dest: &Path, transfer: &Transfer) -> Result<(), String> {
+    let url = url.trim();
+    let source = if url.starts_with('~') || url.starts_with('/') {
+        crate::utils::path::expand_tilde(url)
+            .to_string_lossy()
+            .into_owned()
+    } else {
+        url.to_string()
+    };
+    RepoBuilder::new()
+        .fetch_options(transfer.fetch_options())
+        .clone(&source, dest)
+        .map(|_| ())
+        .map_err(|e| {
+            let _ = std::fs::remove_dir_all(dest);
+            transfer.error(&format!("Failed to clone {}", url), e)
+        })
+}
+
+/// The `origin` URL of a theme installed from git.
+pub fn origin_url(theme_dir: &Path) -> Option<String> {
+    let repo = Repository::open(theme_dir).ok()?;
+    let remote = repo.find_remote("origin").ok()?;
+    remote.url().ok().map(str::to_string)
+}
+
+/// Fetches `origin` and fast-forwards the checked out branch. Local commits
+/// or uncommitted edits to files that changed upstream stop the update.
+/// Returns a short description of what happened.
+pub fn pull_theme(theme_dir: &Path, transfer: &Transfer) -> Result<String, String> {
+    let err = |e: git2::Error| e.message().to_string();
+    let repo = Repository::open(theme_dir).map_err(err)?;
+    let head = repo.head().map_err(err)?;
+    if !head.is_branch() {
+        return Err("HEAD is not on a branch".to_string());
+    }
+    let branch = head.shorthand().map_err(err)?.to_string();
+
+    let mut remote = repo.find_remote("origin").map_err(err)?;
+    remote
+        .fetch(&[&branch], Some(&mut transfer.fetch_options()), None)
+        .map_err(|e| transfer.error("Failed to fetch", e))?;
+
+    let fetch_head = repo.find_reference("FETCH_HEAD").map_err(err)?;
+    let upstream = repo
+        .reference_to_annotated_commit(&fetch_head)
+        .map_err(err)?;
+    let (analysis, _) = repo.merge_analysis(&[&upstream]).map_err(err)?;
+
+    if analysis.is_up_to_date() {
+        return Ok("Already up to date".to_string());
+    }
+    if !analysis.is_fast_forward() {
+        return Err("Local commits differ from upstream, cannot fast-forward".to_string());
+    }
+
+    let target = repo.find_object(upstream.id(), None).map_err(err)?;
+    repo.checkout_tree(&target, Some(CheckoutBuilder::new().safe()))
+        .map_err(|e| format!("Update would overwrite local changes: {}", e.message()))?;
+    let mut head_ref = repo.head().map_err(err)?;
+    head_ref
+        .set_target(upstream.id(), "fast-forward from origin")
+        .map_err(err)?;
+
+    let short = upstream.id().to_string();
+    Ok(format!("Updated to {}", &short[..7]))
+}
+
+/// What a background git job does, to finish up once it is done.
+#[derive(Clone, Debug)]
+pub enum GitJobKind {
+    Clone { url: String, theme: String },
+    Pull { theme: String },
+}
+
+/// A clone or pull running on a worker thread. `result` receives a short
+/// description of the outcome.
+pub struct GitJob {
+    pub kind: GitJobKind,
+    pub transfer: Arc<Transfer>,
+    pub result: Receiver<Result<String, String>>,
+}
+
+fn spawn_job(
+    kind: GitJobKind,
+    run: impl FnOnce(&Transfer) -> Result<String, String> + Send + 'static,
+) -> GitJob {
+    let transfer = Arc::new(Transfer::default());
+    let (tx, rx) = mpsc::channel();
+    let worker_transfer = Arc::clone(&transfer);
+    std::thread::spawn(move || {
+        let _ = tx.send(run(&worker_transfer));
+    });
+    GitJob {
+        kind,
+        transfer,
+        result: rx,
+    }
+}
+
+/// Clones `url` into `dest` in the background as theme `theme`.
+pub fn spawn_clone(url: &str, dest: PathBuf, theme: &str) -> GitJob {
+    let source = url.to_string();
+    spawn_job(
+        GitJobKind::Clone {
+            url: url.trim().to_string(),
+            theme: theme.to_string(),
+        },
+        move |transfer| {
+            clone_theme(&source, &dest, transfer)?;
+            eprintln!("[DEBUG] Cloned {} into {}", source.trim(), dest.display());
+            Ok(format!("Installed from {}", source.trim()))
+        },
+    )
+}
+
+/// Pulls updates of the theme at `theme_dir` in the background.
+pub fn spawn_pull(theme_dir: PathBuf, theme: &str) -> GitJob {
+    spawn_job(
+        GitJobKind::Pull {
+            theme: theme.to_string(),
+        },
+        move |transfer| pull_theme(&theme_dir, transfer),
+    )
+}
+
+/// One entry of a theme's version history.
+#[derive(Clone, Debug)]
+pub struct CommitInfo {
+    pub id: String,
+    pub summary: String,
+    /// Seconds since the Unix epoch
+    pub time: i64,
+}
+
+impl CommitInfo {
+    pub fn short_id(&self) -> &str {
+        &self.id[..7.min(self.id.len())]
+    }
+}
+
+fn signature(repo: &Repository) -> Result<Signature<'static>, git2::Error> {
+    repo.signature()
+        .or_else(|_| Signature::now("Omarchy Theme Maker", "theme-maker@localhost"))
+}
+
+/// Refuses history commits in a clone of an installed theme, they would
+/// stop `pull_theme` from fast-forwarding.
+fn ensure_no_origin(repo: &Repository) -> Result<(), String> {
+    if repo.find_remote("origin").is_ok() {
+        return Err(
+            "Version history is not kept for themes installed from git, use Pull updates"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// Commits the current content of a theme folder, creating the repository
+/// on first use. Returns `None` when nothing changed since the last commit.
+/// Themes installed from git are refused, check `origin_url` first.
+pub fn commit_theme(theme_dir: &Path, message: &str) -> Result<Option<String>, String> {
+    let err = |e: git2::Error| format!("Version history: {}", e.message());
+    let repo = match Repository::open(theme_dir) {
+        Ok(repo) => repo,
+        Err(_) => Repository::init(theme_dir).map_err(err)?,
+    };
+    ensure_no_origin(&repo)?;
+
+    let mut index = repo.index().map_err(err)?;
+    index
+        .add_all(["*"], IndexAddOption::DEFAULT, None)
+        .map_err(err)?;
+    // Drop files that were deleted from the folder
+    index.update_all(["*"], None).map_err(err)?;
+    index.write().map_err(err)?;
+    let tree = repo
+        .find_tree(index.write_tree().map_err(err)?)
+        .map_err(err)?;
+
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    if parent.as_ref().is_some_and(|p| p.tree_id() == tree.id()) {
+        return Ok(None);
+    }
+
+    let sig = signature(&repo).map_err(err)?;
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    let id = repo
+        .commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+        .map_err(err)?;
+    Ok(Some(id.to_string()))
+}
+
+/// Commits of a theme, newest first. Empty if the theme has no repository.
+pub fn theme_history(theme_dir: &Path) -> Result<Vec<CommitInfo>, String> {
+    let Ok(repo) = Repository::open(theme_dir) else {
+        return Ok(vec![]);
+    };
+    if repo.head().is_err() {
+        return Ok(vec![]);
+    }
+    let err = |e: git2::Error| e.message().to_string();
+    let mut walk = repo.revwalk().map_err(err)?;
+    walk.push_head().map_err(err)?;
+
+    walk.map(|oid| {
+        let commit = repo.find_commit(oid.map_err(err)?).map_err(err)?;
+        Ok(CommitInfo {
+            id: commit.id().to_string(),
+            summary: commit
+                .summary()
+                .ok()
+                .flatten()
+                .unwrap_or_default()
+                .to_string(),
+            time: commit.time().seconds(),
+        })
+    })
+    .collect()
+}
+
+/// Unified diff of a commit against its parent.
+pub fn commit_diff(theme_dir: &Path, id: &str) -> Result<String, String> {
+    let err = |e: git2::Error| e.message().to_string();
+    let repo = Repository::open(theme_dir).map_err(err)?;
+    let commit = repo
+        .find_commit(Oid::from_str(id).map_err(err)?)
+        .map_err(err)?;
+    let tree = commit.tree().map_err(err)?;
+    let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+
+    let diff = repo
+        .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+        .map_err(err)?;
+    let mut text = String::new();
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            text.push(line.origin());
+        }
+        text.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    })
+    .map_err(err)?;
+    Ok(text)
+}
+
+/// Restores the files of commit `id` and records that as a new commit, so
+/// the newer versions stay in the history.
+pub fn revert_theme_to(theme_dir: &Path, id: &str) -> Result<(), String> {
+    let err = |e: git2::Error| e.message().to_string();
+    let repo = Repository::open(theme_dir).map_err(err)?;
+    ensure_no_origin(&repo)?;
+    let commit = repo
+        .find_commit(Oid::from_str(id).map_err(err)?)
+        .map_err(err)?;
+    repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().force()))
+        .map_err(err)?;
+
+    let short = &id[..7.min(id.len())];
+    commit_theme(theme_dir, &format!("Revert to {}", short))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Empty scratch folder unique to this test process.
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("omarchy-git-test-{}", std::process::id()))
+            .join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Commits `content` as `file` in the working copy at `dir` and pushes it
+    /// to its origin.
+    fn push_file(dir: &Path, file: &str, content: &str) {
+        std::fs::write(dir.join(file), content).unwrap();
+        let repo = Repository::open(dir).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(file)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("test", "test@localhost").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, file, &tree, &parents)
+            .unwrap();
+        let branch = repo.head().unwrap().name().unwrap().to_string();
+        repo.find_remote("origin")
+            .unwrap()
+            .push(&[format!("{0}:{0}", branch)], None)
+            .unwrap();
+    }
+
+    /// A bare upstream repository with one commit, and the working copy
+    /// used to push to it.
+    fn upstream(name: &str) -> (PathBuf, PathBuf) {
+        let root = scratch(name);
+        let bare = root.join("upstream.git");
+        let work = root.join("work");
+        Repository::init_bare(&bare).unwrap();
+        Repository::clone(bare.to_str().unwrap(), &work).unwrap();
+        push_file(&work, "colors.toml", "accent = \"#89b4fa\"\n");
+        (bare, work)
+    }
+
+    #[test]
+    fn clones_from_a_bare_repo() {
+        let (bare, _) = upstream("clone");
+        let dest = bare.with_file_name("themes").join("nord");
+
+        clone_theme(bare.to_str().unwrap(), &dest, &Transfer::default()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dest.join("colors.toml")).unwrap(),
+            "accent = \"#89b4fa\"\n"
+        );
+        assert_eq!(origin_url(&dest).as_deref(), bare.to_str());
+    }
+
+    #[test]
+    fn failed_clone_leaves_no_folder() {
+        let root = scratch("clone-missing");
+        let dest = root.join("nord");
+        let missing = root.join("missing.git");
+
+        assert!(clone_theme(missing.to_str().unwrap(), &dest, &Transfer::default()).is_err());
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn cancelled_clone_stops() {
+        let (bare, _) = upstream("clone-cancel");
+        let dest = bare.with_file_name("nord");
+        let transfer = Transfer::default();
+        transfer.cancel();
+
+        let url = format!("file://{}", bare.display());
+        assert_eq!(
+            clone_theme(&url, &dest, &transfer),
+            Err("Cancelled".to_string())
+        );
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn pull_fast_forwards_to_upstream() {
+        let (bare, work) = upstream("pull");
+        let theme = bare.with_file_name("nord");
+        clone_theme(bare.to_str().unwrap(), &theme, &Transfer::default()).unwrap();
+
+        assert_eq!(
+            pull_theme(&theme, &Transfer::default()).unwrap(),
+            "Already up to date"
+        );
+        push_file(&work, "colors.toml", "accent = \"#a3be8c\"\n");
+        let outcome = pull_theme(&theme, &Transfer::default()).unwrap();
+        assert!(outcome.starts_with("Updated to "), "{}", outcome);
+        assert_eq!(
+            std::fs::read_to_string(theme.join("colors.toml")).unwrap(),
+            "accent = \"#a3be8c\"\n"
+        );
+    }
+
+    #[test]
+    fn commits_only_when_files_change() {
+        let theme = scratch("commit");
+        std::fs::write(theme.join("colors.toml"), "accent = \"#89b4fa\"\n").unwrap();
+
+        assert!(commit_theme(&theme, "Save nord").unwrap().is_some());
+        assert_eq!(commit_theme(&theme, "Save nord").unwrap(), None);
+        std::fs::write(theme.join("colors.toml"), "accent = \"#a3be8c\"\n").unwrap();
+        assert!(commit_theme(&theme, "Overwrite nord").unwrap().is_some());
+
+        let history = theme_history(&theme).unwrap();
+        let summaries: Vec<&str> = history.iter().map(|c| c.summary.as_str()).collect();
+        assert_eq!(summaries, ["Overwrite nord", "Save nord"]);
+    }
+
+    #[test]
+    fn revert_restores_files_as_a_new_commit() {
+        let theme = scratch("revert");
+        std::fs::write(theme.join("colors.toml"), "accent = \"#89b4fa\"\n").unwrap();
+        let first = commit_theme(&theme, "Save nord").unwrap().unwrap();
+        std::fs::write(theme.join("colors.toml"), "accent = \"#a3be8c\"\n").unwrap();
+        commit_theme(&theme, "Overwrite nord").unwrap();
+
+        revert_theme_to(&theme, &first).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(theme.join("colors.toml")).unwrap(),
+            "accent = \"#89b4fa\"\n"
+        );
+        assert_eq!(theme_history(&theme).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn installed_themes_keep_pulling() {
+        let (bare, work) = upstream("installed");
+        let theme = bare.with_file_name("nord");
+        clone_theme(bare.to_str().unwrap(), &theme, &Transfer::default()).unwrap();
+        let first = theme_history(&theme).unwrap()[0].id.clone();
+
+        assert!(commit_theme(&theme, "Save nord").is_err());
+        assert!(revert_theme_to(&theme, &first).is_err());
+        push_file(&work, "colors.toml", "accent = \"#a3be8c\"\n");
+        let outcome = pull_theme(&theme, &Transfer::default()).unwrap();
+        assert!(outcome.starts_with("Updated to "), "{}", outcome);
+    }
+
+    #[test]
+    fn background_clone_reports_the_result() {
+        let (bare, _) = upstream("spawn-clone");
+        let dest = bare.with_file_name("nord");
+
+        let job = spawn_clone(bare.to_str().unwrap(), dest.clone(), "nord");
+        let outcome = job
+            .result
+            .recv_timeout(std::time::Duration::from_secs(10))
+            .unwrap();
+        assert!(outcome.is_ok(), "{:?}", outcome);
+        assert!(dest.join("colors.toml").exists());
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use egui::Color32;
+
+use crate::theme::compare::{colors_by_key, numbered_keys};
+use crate::utils::color::{format_like, map_colors_in_content};
+
+/// A color of the source theme that can replace the target's value for the
+/// same key.
+#[derive(Clone, Debug)]
+pub struct MergeKey {
+    pub key: String,
+    pub current: Color32,
+    pub incoming: Color32,
+    pub selected: bool,
+}
+
+/// A source file that differs from the target. It is either copied whole or
+/// merged key by key.
+#[derive(Clone, Debug)]
+pub struct MergeFile {
+    pub file: String,
+    pub in_target: bool,
+    pub whole_file: bool,
+    pub keys: Vec<MergeKey>,
+}
+
+/// What to take from another theme, with nothing selected at first.
+#[derive(Clone, Debug)]
+pub struct MergePlan {
+    pub source: String,
+    source_cache: HashMap<String, String>,
+    pub files: Vec<MergeFile>,
+}
+
+impl MergePlan {
+    pub fn new(
+        source: &str,
+        source_cache: HashMap<String, String>,
+        target_cache: &HashMap<String, String>,
+    ) -> Self {
+        let mut names: Vec<&String> = source_cache.keys().collect();
+        names.sort();
+
+        let files = names
+            .into_iter()
+            .filter(|file| target_cache.get(*file) != source_cache.get(*file))
+            .map(|file| {
+                let incoming = colors_by_key(file, &source_cache[file]);
+                let keys = match target_cache.get(file) {
+                    Some(target) => numbered_keys(file, target)
+                        .into_iter()
+                        .filter_map(|(key, c)| {
+                            let new_color = *incoming.get(&key)?;
+                            (new_color != c.color.value).then_some(MergeKey {
+                                key,
+                                current: c.color.value,
+                                incoming: new_color,
+                                selected: false,
+                            })
+                        })
+                        .collect(),
+                    None => vec![],
+                };
+                MergeFile {
+                    file: file.clone(),
+                    in_target: target_cache.contains_key(file),
+                    whole_file: false,
+                    keys,
+                }
+            })
+            .collect();
+
+        Self {
+            source: source.to_string(),
+            source_cache,
+            files,
+        }
+    }
+
+    pub fn has_selection(&self) -> bool {
+        self.files
+            .iter()
+            .any(|f| f.whole_file || f.keys.iter().any(|k| k.selected))
+    }
+
+    /// The target files with the selection applied. Merged keys keep the
+    /// target's color format, so a `rgba()` value stays `rgba()` even when
+    /// the source wrote hex.
+    pub fn apply(&self, target_cache: &HashMap<String, String>) -> HashMap<String, String> {
+        let mut merged = target_cache.clone();
+        for file in &self.files {
+            if file.whole_file {
+                merged.insert(file.file.clone(), self.source_cache[&file.file].clone());
+                continue;
+            }
+            let selected: HashMap<&str, Color32> = file
+                .keys
+                .iter()
+                .filter(|k| k.selected)
+                .map(|k| (k.key.as_str(), k.incoming))
+                .collect();
+            let Some(content) = target_cache
+                .get(&file.file)
+                .filter(|_| !selected.is_empty())
+            else {
+                continue;
+            };
+
+            let keys: HashMap<(usize, usize), String> = numbered_keys(&file.file, content)
+                .into_iter()
+                .map(|(key, c)| ((c.color.line, c.color.start_col), key))
+                .collect();
+            let new_content = map_colors_in_content(content, |detected| {
+                let key = keys.get(&(detected.line, detected.start_col))?;
+                let color = selected.get(key.as_str())?;
+                Some(format_like(*color, &detected.hex_text))
+            });
+            merged.insert(file.file.clone(), new_content);
+        }
+        merged
+    }
+}
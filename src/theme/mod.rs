@@ -1,4 +1,21 @@
+pub mod adjust;
+pub mod apply;
+pub mod archive;
+pub mod base16;
+pub mod compare;
+pub mod contrast;
+pub mod export;
+pub mod git;
+pub mod merge;
+pub mod ops;
+pub mod roots;
 pub mod scanner;
+pub mod search;
+pub mod semantic;
+pub mod similarity;
+pub mod thumbnail;
+pub mod variant;
+pub mod wal;
 
 pub use crate::utils::color::detect_colors_in_content;
-pub use scanner::{scan_theme_files, scan_themes_dir};
+pub use scanner::scan_theme_files;
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::utils::path::expand_tilde;
+
+/// Hidden folder inside the themes directory that deleted themes are moved to.
+/// Being hidden keeps it out of `scan_themes_dir`.
+pub const TRASH_DIR: &str = ".trash";
+
+/// Copies `src` into `dst`, skipping hidden entries like `.git` the way the
+/// theme scanner does, so a copy doesn't follow the original's repository.
+pub fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dst)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let ty = entry.file_type()?;
+        let dest_path = dst.join(entry.file_name());
+        if ty.is_dir() {
+            copy_dir_all(&entry.path(), &dest_path)?;
+        } else {
+            std::fs::copy(entry.path(), dest_path)?;
+        }
+    }
+    Ok(())
+}
+
+fn theme_dir(themes_path: &str, name: &str) -> PathBuf {
+    expand_tilde(themes_path).join(name)
+}
+
+pub fn validate_theme_name(name: &str) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Theme name cannot be empty".to_string());
+    }
+    if name.starts_with('.') {
+        return Err("Theme name cannot start with '.'".to_string());
+    }
+    if name.contains('/') || name.contains('\\') {
+        return Err("Theme name cannot contain path separators".to_string());
+    }
+    Ok(())
+}
+
+/// Whether a theme folder named `name` exists in any of `roots`.
+pub fn theme_exists(roots: &[String], name: &str) -> bool {
+    roots.iter().any(|root| theme_dir(root, name).exists())
+}
+
+/// Returns `base` if no theme in `roots` uses it yet, otherwise the first
+/// free `base-2`, `base-3`, ...
+pub fn unique_theme_name(roots: &[String], base: &str) -> String {
+    let base = base.trim();
+    if !theme_exists(roots, base) {
+        return base.to_string();
+    }
+    let mut n = 2;
+    loop {
+        let candidate = format!("{}-{}", base, n);
+        if !theme_exists(roots, &candidate) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+/// Validates `new_name` and makes sure no theme folder uses it yet, so nothing
+/// is ever written over silently. Returns the folder to create.
+pub fn check_new_name(themes_path: &str, new_name: &str) -> Result<PathBuf, String> {
+    validate_theme_name(new_name)?;
+    let dest = theme_dir(themes_path, new_name.trim());
+    if dest.exists() {
+        return Err(format!(
+            "A theme named '{}' already exists",
+            new_name.trim()
+        ));
+    }
+    Ok(dest)
+}
+
+/// Writes `files` (relative path -> content) below `theme_dir`, recreating
+/// subfolders.
+pub fn write_theme_files(theme_dir: &Path, files: &HashMap<String, String>) -> Result<(), String> {
+    for (file_name, content) in files {
+        let file_path = theme_dir.join(file_name);
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(&file_path, content).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+pub fn rename_theme(themes_path: &str, old_name: &str, new_name: &str) -> Result<(), String> {
+    let src = theme_dir(themes_path, old_name);
+    let dest = check_new_name(themes_path, new_name)?;
+    std::fs::rename(&src, &dest).map_err(|e| format!("Failed to rename {}: {}", old_name, e))
+}
+
+/// Copies theme `name` of `src_root` into `dest_root` as `new_name`.
+pub fn duplicate_theme(
+    src_root: &str,
+    name: &str,
+    dest_root: &str,
+    new_name: &str,
+) -> Result<(), String> {
+    let src = theme_dir(src_root, name);
+    let dest = check_new_name(dest_root, new_name)?;
+    copy_dir_all(&src, &dest).map_err(|e| format!("Failed to duplicate {}: {}", name, e))
+}
+
+/// Moves a theme into the trash folder, suffixed with a timestamp so repeated
+/// deletions of the same name never collide. Returns the trashed location.
+pub fn trash_theme(themes_path: &str, name: &str) -> Result<PathBuf, String> {
+    let src = theme_dir(themes_path, name);
+    if !src.exists() {
+        return Err(format!("Theme folder does not exist: {}", src.display()));
+    }
+
+    let trash = expand_tilde(themes_path).join(TRASH_DIR);
+    std::fs::create_dir_all(&trash).map_err(|e| e.to_string())?;
+
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut dest = trash.join(format!("{}-{}", name, stamp));
+    let mut n = 2;
+    while dest.exists() {
+        dest = trash.join(format!("{}-{}-{}", name, stamp, n));
+        n += 1;
+    }
+
+    std::fs::rename(&src, &dest).map_err(|e| format!("Failed to move {} to trash: {}", name, e))?;
+    Ok(dest)
+}
+
+pub fn open_folder(path: &Path) -> Result<(), String> {
+    std::process::Command::new("xdg-open")
+        .arg(path)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicate_leaves_the_repository_behind() {
+        let root = std::env::temp_dir().join(format!("omarchy-ops-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let theme = root.join("nord");
+        std::fs::create_dir_all(theme.join("backgrounds")).unwrap();
+        git2::Repository::init(&theme).unwrap();
+        std::fs::write(theme.join("colors.toml"), "").unwrap();
+        std::fs::write(theme.join("backgrounds/1.png"), "").unwrap();
+        let root_path = root.to_string_lossy();
+
+        duplicate_theme(&root_path, "nord", &root_path, "nord-copy").unwrap();
+        let copy = root.join("nord-copy");
+        assert!(copy.join("colors.toml").exists());
+        assert!(copy.join("backgrounds/1.png").exists());
+        assert!(!copy.join(".git").exists());
+        assert!(git2::Repository::open(&copy).is_err());
+    }
+}
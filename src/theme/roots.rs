@@ -0,0 +1,159 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::theme::scanner::scan_themes_dir;
+use crate::utils::path::expand_tilde;
+
+/// A folder whose subfolders are listed as themes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThemeRoot {
+    pub path: String,
+    /// Missing or not writable by the user, e.g. a system theme folder
+    pub read_only: bool,
+    /// Listed in the protected folders, its themes are kept as they are
+    pub protected: bool,
+}
+
+impl ThemeRoot {
+    pub fn new(path: &str, protected_roots: &[String]) -> Self {
+        Self {
+            path: path.to_string(),
+            read_only: !is_writable(&expand_tilde(path)),
+            protected: protected_roots.iter().any(|p| same_dir(p, path)),
+        }
+    }
+
+    /// Whether themes in this root can be changed, renamed or deleted, and
+    /// new themes saved into it.
+    pub fn is_locked(&self) -> bool {
+        self.read_only || self.protected
+    }
+}
+
+/// Why a theme must not be changed in place.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Protection {
+    /// Listed in the protected themes
+    Listed,
+    /// In a protected theme folder
+    Root(String),
+    /// The theme folder is a symlink to this package-managed folder
+    PackageLink(PathBuf),
+}
+
+impl Protection {
+    pub fn describe(&self) -> String {
+        match self {
+            Protection::Listed => "is a protected theme".to_string(),
+            Protection::Root(root) => format!("is in a protected folder ({})", root),
+            Protection::PackageLink(target) => {
+                format!("links to {}, which is replaced on update", target.display())
+            }
+        }
+    }
+}
+
+/// Resolves `~` and symlinks so differently written paths compare equal.
+fn normalize(path: &str) -> PathBuf {
+    let expanded = expand_tilde(path.trim());
+    expanded.canonicalize().unwrap_or(expanded)
+}
+
+fn same_dir(a: &str, b: &str) -> bool {
+    normalize(a) == normalize(b)
+}
+
+/// Where `theme_dir` points to if it is a symlink into one of
+/// `package_paths`, like the stock themes Omarchy links from its install.
+pub fn package_link_target(theme_dir: &Path, package_paths: &[String]) -> Option<PathBuf> {
+    let is_link = std::fs::symlink_metadata(theme_dir)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false);
+    if !is_link {
+        return None;
+    }
+    let target = theme_dir.canonicalize().ok()?;
+    package_paths
+        .iter()
+        .filter(|p| !p.trim().is_empty())
+        .any(|p| target.starts_with(normalize(p)))
+        .then_some(target)
+}
+
+/// Protection of theme `name` found in `root`, the first reason that applies.
+pub fn theme_protection(
+    root: &ThemeRoot,
+    name: &str,
+    protected_themes: &[String],
+    package_paths: &[String],
+) -> Option<Protection> {
+    if protected_themes.iter().any(|t| t.trim() == name) {
+        return Some(Protection::Listed);
+    }
+    if root.protected {
+        return Some(Protection::Root(root.path.clone()));
+    }
+    package_link_target(&expand_tilde(&root.path).join(name), package_paths)
+        .map(Protection::PackageLink)
+}
+
+/// Whether files can be created in `dir`. Asks the kernel with
+/// faccessat(2), which unlike the permission bits also knows about
+/// read-only mounts and ACLs, without writing anything into the folder.
+pub fn is_writable(dir: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    if !dir.is_dir() {
+        return false;
+    }
+    let Ok(path) = std::ffi::CString::new(dir.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: `path` is a NUL terminated string that outlives the call
+    unsafe {
+        libc::faccessat(
+            libc::AT_FDCWD,
+            path.as_ptr(),
+            libc::W_OK | libc::X_OK,
+            libc::AT_EACCESS,
+        ) == 0
+    }
+}
+
+/// Themes of all roots with the index of the root they are in. A name found
+/// in several roots resolves to the first root, like commands in `$PATH`.
+pub fn scan_roots(roots: &[ThemeRoot]) -> Vec<(String, usize)> {
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut themes = Vec::new();
+    for (i, root) in roots.iter().enumerate() {
+        for name in scan_themes_dir(&root.path) {
+            if seen.insert(name.clone()) {
+                themes.push((name, i));
+            } else {
+                eprintln!(
+                    "[DEBUG] Theme {} in {} is hidden by an earlier root",
+                    name, root.path
+                );
+            }
+        }
+    }
+    themes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writable_check_leaves_the_folder_alone() {
+        let dir = std::env::temp_dir().join(format!("omarchy-roots-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        assert!(is_writable(&dir));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        assert!(!is_writable(&dir.join("missing")));
+        std::fs::write(dir.join("colors.toml"), "").unwrap();
+        assert!(!is_writable(&dir.join("colors.toml")));
+    }
+}
@@ -1,5 +1,21 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::Path;
+
 use crate::utils::path::expand_tilde;
 
+const MAX_SCAN_DEPTH: usize = 8;
+
+/// Extensions that are never treated as editable text, even when their
+/// content happens to be valid UTF-8 (e.g. `.svg`).
+const NON_TEXT_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "webp", "svg", "svgz", "ico", "icns", "bmp", "tif", "tiff",
+    "avif", "heic", "xpm", "ttf", "otf", "woff", "woff2", "gz", "zip", "xz", "zst",
+];
+
+/// How many bytes are sniffed when deciding whether a file is text.
+const SNIFF_LEN: usize = 8192;
+
 pub fn scan_themes_dir(path: &str) -> Vec<String> {
     let expanded = expand_tilde(path);
     if !expanded.exists() {
@@ -17,24 +33,113 @@ pub fn scan_themes_dir(path: &str) -> Vec<String> {
     }
 }
 
+/// Lists every file of a theme, recursing into subfolders. Returned names are
+/// relative to the theme folder and always use `/` as separator.
 pub fn scan_theme_files(theme_path: &str) -> Vec<String> {
     let expanded = expand_tilde(theme_path);
     if !expanded.exists() {
         return vec![];
     }
-    if let Ok(entries) = std::fs::read_dir(&expanded) {
-        entries
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().is_file())
-            .filter_map(|e| e.file_name().into_string().ok())
-            .filter(|name| !name.starts_with('.'))
-            .filter(|name| !name.ends_with(".png") && !name.ends_with(".jpg"))
-            .collect()
-    } else {
-        vec![]
+    let mut files = Vec::new();
+    collect_theme_files(&expanded, "", 0, &mut files);
+    files
+}
+
+fn collect_theme_files(dir: &Path, prefix: &str, depth: usize, files: &mut Vec<String>) {
+    if depth > MAX_SCAN_DEPTH {
+        return;
+    }
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let name = match entry.file_name().into_string() {
+                Ok(name) => name,
+                Err(_) => continue,
+            };
+            if name.starts_with('.') {
+                continue;
+            }
+
+            let relative = if prefix.is_empty() {
+                name.clone()
+            } else {
+                format!("{}/{}", prefix, name)
+            };
+
+            let path = entry.path();
+            if path.is_dir() {
+                collect_theme_files(&path, &relative, depth + 1, files);
+            } else if path.is_file() {
+                files.push(relative);
+            }
+        }
+    }
+}
+
+/// Reads the text files of a theme into memory. Returns the contents keyed by
+/// relative file name, and the names of files that are not editable text.
+pub fn read_theme_files(
+    theme_path: &str,
+    files: &[String],
+) -> (HashMap<String, String>, HashSet<String>) {
+    let mut cache = HashMap::new();
+    let mut non_editable = HashSet::new();
+
+    for file_name in files {
+        let file_path = format!("{}/{}", theme_path, file_name);
+        let expanded = expand_tilde(&file_path);
+        eprintln!("[DEBUG] Preloading file: {:?}", expanded);
+        let content = if is_text_file(&expanded) {
+            std::fs::read_to_string(&expanded).ok()
+        } else {
+            None
+        };
+        if let Some(content) = content {
+            let bytes = content.len();
+            cache.insert(file_name.clone(), content);
+            eprintln!("[DEBUG] Cached {} ({} bytes)", file_name, bytes);
+        } else {
+            non_editable.insert(file_name.clone());
+            eprintln!("[DEBUG] Not a text file: {}", file_name);
+        }
     }
+
+    (cache, non_editable)
+}
+
+/// Scans a theme folder and reads all of its text files.
+pub fn load_theme_cache(theme_path: &str) -> HashMap<String, String> {
+    read_theme_files(theme_path, &scan_theme_files(theme_path)).0
 }
 
 pub fn get_extension(name: &str) -> String {
-    name.rsplit('.').next().unwrap_or("").to_lowercase()
+    let file_name = name.rsplit('/').next().unwrap_or(name);
+    file_name.rsplit('.').next().unwrap_or("").to_lowercase()
+}
+
+/// Returns true if the file looks like editable text: its extension is not a
+/// known binary/image format and its first bytes are NUL-free UTF-8.
+pub fn is_text_file(path: &Path) -> bool {
+    let name = path.to_string_lossy();
+    if NON_TEXT_EXTENSIONS.contains(&get_extension(&name).as_str()) {
+        return false;
+    }
+
+    let mut buf = Vec::with_capacity(SNIFF_LEN);
+    match std::fs::File::open(path) {
+        Ok(file) => {
+            if file.take(SNIFF_LEN as u64).read_to_end(&mut buf).is_err() {
+                return false;
+            }
+        }
+        Err(_) => return false,
+    }
+
+    if buf.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(&buf) {
+        Ok(_) => true,
+        // A multi-byte character cut off by the sniff window is still text
+        Err(e) => e.error_len().is_none() && buf.len() == SNIFF_LEN,
+    }
 }
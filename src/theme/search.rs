@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use egui::Color32;
+
+use crate::theme::semantic::{theme_keyed_colors, KeyedColor};
+use crate::utils::color::{opaque, parse_hex_color, DetectedColor};
+use crate::utils::colorspace::delta_e;
+
+/// ΔE allowed for a bare hex query, so `#89b4fa` also finds `rgb(137,180,250)`
+/// and rounding differences between formats.
+const EXACT_DELTA_E: f32 = 0.5;
+
+/// A color filter typed in a search box.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColorQuery {
+    /// `#89b4fa` or `#89b4fa~5`: colors within `max_delta_e` (CIEDE2000)
+    Near { color: Color32, max_delta_e: f32 },
+    /// Anything else, matched case-insensitively against keys and color text
+    Text(String),
+}
+
+impl ColorQuery {
+    /// Parses `query`, returning `None` when it is blank or the ΔE after `~`
+    /// is not a number of at least 0.
+    pub fn parse(query: &str) -> Option<Self> {
+        let query = query.trim();
+        if query.is_empty() {
+            return None;
+        }
+
+        let (hex, tolerance) = match query.split_once('~') {
+            Some((hex, tolerance)) => (hex.trim(), Some(tolerance.trim())),
+            None => (query, None),
+        };
+        let is_hex = hex.starts_with('#') || matches!(hex.len(), 6 | 8);
+        if let Some(color) = parse_hex_color(hex).filter(|_| is_hex) {
+            let max_delta_e = match tolerance {
+                Some(t) => t.parse::<f32>().ok().filter(|t| *t >= 0.0)?,
+                None => EXACT_DELTA_E,
+            };
+            return Some(ColorQuery::Near { color, max_delta_e });
+        }
+
+        Some(ColorQuery::Text(query.to_lowercase()))
+    }
+
+    /// ΔE to the query color, or 0 for a text match. `None` if it doesn't match.
+    pub fn distance(&self, key: &str, color: &DetectedColor) -> Option<f32> {
+        match self {
+            ColorQuery::Near {
+                color: target,
+                max_delta_e,
+            } => {
+                let de = delta_e(opaque(*target), opaque(color.value));
+                (de <= *max_delta_e).then_some(de)
+            }
+            ColorQuery::Text(text) => (key.to_lowercase().contains(text)
+                || color.hex_text.to_lowercase().contains(text))
+            .then_some(0.0),
+        }
+    }
+
+    pub fn matches(&self, key: &str, color: &DetectedColor) -> bool {
+        self.distance(key, color).is_some()
+    }
+}
+
+/// Case-insensitive substring filter for theme and file names. A file
+/// extension such as `.css` or `css` works the same way.
+pub fn name_matches(filter: &str, name: &str) -> bool {
+    let filter = filter.trim();
+    filter.is_empty() || name.to_lowercase().contains(&filter.to_lowercase())
+}
+
+/// A color of a theme found by a search.
+#[derive(Clone, Debug)]
+pub struct ColorMatch {
+    pub file: String,
+    pub key: String,
+    pub color: Color32,
+    pub delta_e: f32,
+}
+
+/// The colors of one theme matching a query, closest first.
+#[derive(Clone, Debug)]
+pub struct ThemeSearchResult {
+    pub theme: String,
+    pub matches: Vec<ColorMatch>,
+}
+
+pub fn search_theme(file_cache: &HashMap<String, String>, query: &ColorQuery) -> Vec<ColorMatch> {
+    let mut matches: Vec<ColorMatch> = theme_keyed_colors(file_cache)
+        .into_iter()
+        .filter_map(|KeyedColor { file, key, color }| {
+            let delta_e = query.distance(&key, &color)?;
+            Some(ColorMatch {
+                file,
+                key,
+                color: color.value,
+                delta_e,
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| a.delta_e.total_cmp(&b.delta_e));
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn near(hex: &str, max_delta_e: f32) -> Option<ColorQuery> {
+        Some(ColorQuery::Near {
+            color: parse_hex_color(hex).unwrap(),
+            max_delta_e,
+        })
+    }
+
+    #[test]
+    fn parses_hex_queries() {
+        assert_eq!(ColorQuery::parse("#89b4fa"), near("#89b4fa", EXACT_DELTA_E));
+        assert_eq!(ColorQuery::parse("89b4fa"), near("#89b4fa", EXACT_DELTA_E));
+        assert_eq!(ColorQuery::parse("#89b4fa~5"), near("#89b4fa", 5.0));
+        assert_eq!(ColorQuery::parse("  #89b4fa ~ 2.5 "), near("#89b4fa", 2.5));
+        assert_eq!(ColorQuery::parse("#89b4fa~0"), near("#89b4fa", 0.0));
+    }
+
+    #[test]
+    fn other_text_searches_keys() {
+        assert_eq!(
+            ColorQuery::parse(" Accent "),
+            Some(ColorQuery::Text("accent".to_string()))
+        );
+        assert_eq!(
+            ColorQuery::parse("bg~2"),
+            Some(ColorQuery::Text("bg~2".to_string()))
+        );
+        assert_eq!(
+            ColorQuery::parse("89b4"),
+            Some(ColorQuery::Text("89b4".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_blank_queries_and_bad_delta_e() {
+        assert_eq!(ColorQuery::parse(""), None);
+        assert_eq!(ColorQuery::parse("   "), None);
+        assert_eq!(ColorQuery::parse("#89b4fa~"), None);
+        assert_eq!(ColorQuery::parse("#89b4fa~far"), None);
+        assert_eq!(ColorQuery::parse("#89b4fa~-1"), None);
+        assert_eq!(ColorQuery::parse("#89b4fa~NaN"), None);
+    }
+}
@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+
+use egui::Color32;
+
+use crate::utils::color::{
+    detect_colors_in_content, format_like, map_colors_in_content, opaque, DetectedColor,
+};
+use crate::utils::colorspace::delta_e;
+use crate::utils::cvd::{simulate, CvdMode};
+
+/// A detected color together with the config key it is assigned to, e.g.
+/// `colors.normal.red` in `alacritty.toml` or `theme[main_bg]` in `btop.theme`.
+#[derive(Clone, Debug)]
+pub struct KeyedColor {
+    pub file: String,
+    pub key: String,
+    pub color: DetectedColor,
+}
+
+/// What a color is used for, independent of the app config it appears in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ColorRole {
+    Background,
+    Foreground,
+    Cursor,
+    Selection,
+    Accent,
+    ActiveBorder,
+    InactiveBorder,
+    /// ANSI palette entry 0-15
+    Ansi(u8),
+}
+
+const ANSI_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// Extracts the key text in front of the first color on a line.
+fn key_before(prefix: &str) -> String {
+    let trimmed = prefix.trim_end_matches(|c: char| {
+        c.is_whitespace() || c == '=' || c == ':' || c == '"' || c == '\'' || c == ','
+    });
+    let mut tokens = trimmed
+        .split(|c: char| c.is_whitespace() || c == '=' || c == ',' || c == '{')
+        .filter(|t| !t.is_empty())
+        .map(|t| t.trim_matches(|c| c == '"' || c == '\''));
+
+    let last = tokens.next_back().unwrap_or_default();
+    // ghostty `palette = 0=#...`
+    if !last.is_empty() && last.chars().all(|c| c.is_ascii_digit()) {
+        if let Some(prev) = tokens.next_back() {
+            return format!("{}{}", prev, last);
+        }
+    }
+    last.to_string()
+}
+
+/// Finds the colors in `content` and the key each one is assigned to.
+/// TOML/INI `[sections]` and `selector {` blocks are prepended to the key.
+pub fn keyed_colors(file: &str, content: &str) -> Vec<KeyedColor> {
+    let colors = detect_colors_in_content(content);
+    let mut result = Vec::with_capacity(colors.len());
+
+    let mut sections: Vec<String> = Vec::new();
+    let mut ini_section = String::new();
+    let mut color_iter = colors.into_iter().peekable();
+
+    for (line_idx, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            ini_section = trimmed[1..trimmed.len() - 1].trim().to_string();
+        }
+
+        let mut line_key: Option<String> = None;
+        while let Some(color) = color_iter.next_if(|c| c.line == line_idx) {
+            let key = line_key
+                .get_or_insert_with(|| {
+                    let name = key_before(&line[..color.start_col]);
+                    let mut scope: Vec<&str> = Vec::new();
+                    if !ini_section.is_empty() {
+                        scope.push(&ini_section);
+                    }
+                    scope.extend(sections.iter().map(|s| s.as_str()));
+                    if name.is_empty() {
+                        scope.join(".")
+                    } else if scope.is_empty() {
+                        name
+                    } else {
+                        format!("{}.{}", scope.join("."), name)
+                    }
+                })
+                .clone();
+            result.push(KeyedColor {
+                file: file.to_string(),
+                key,
+                color,
+            });
+        }
+
+        let opens = trimmed.matches('{').count();
+        let closes = trimmed.matches('}').count();
+        if opens > closes && trimmed.ends_with('{') {
+            let selector = trimmed.trim_end_matches('{').trim();
+            let selector = selector.trim_end_matches('=').trim();
+            sections.push(selector.to_string());
+        } else if closes > opens {
+            for _ in 0..(closes - opens) {
+                sections.pop();
+            }
+        }
+    }
+
+    result
+}
+
+/// Keyed colors of every cached file.
+pub fn theme_keyed_colors(file_cache: &HashMap<String, String>) -> Vec<KeyedColor> {
+    let mut files: Vec<&String> = file_cache.keys().collect();
+    files.sort();
+    files
+        .into_iter()
+        .flat_map(|file| keyed_colors(file, &file_cache[file]))
+        .collect()
+}
+
+/// The last component of a key, without `theme[...]`, `$` or `@` decoration.
+pub fn key_name(key: &str) -> String {
+    let lower = key.to_lowercase();
+    let name = if let Some(inner) = lower
+        .rfind('[')
+        .filter(|_| lower.ends_with(']'))
+        .map(|i| &lower[i + 1..lower.len() - 1])
+    {
+        inner
+    } else if lower.ends_with("col.active_border") {
+        "col.active_border"
+    } else if lower.ends_with("col.inactive_border") {
+        "col.inactive_border"
+    } else {
+        lower.rsplit('.').next().unwrap_or(&lower)
+    };
+    name.trim_start_matches(['$', '@']).to_string()
+}
+
+pub fn role_for_key(key: &str) -> Option<ColorRole> {
+    let lower = key.to_lowercase();
+    let name = key_name(key);
+    let scope = lower.strip_suffix(&name).unwrap_or("");
+    let in_selection = scope.contains("selection");
+    let in_cursor = scope.contains("cursor");
+
+    if let Some(i) = ANSI_NAMES.iter().position(|n| *n == name) {
+        if scope.contains("dim") {
+            return None;
+        }
+        let offset = if scope.contains("bright") { 8 } else { 0 };
+        return Some(ColorRole::Ansi(i as u8 + offset));
+    }
+    for prefix in ["color", "palette"] {
+        if let Some(n) = name.strip_prefix(prefix).and_then(|n| n.parse::<u8>().ok()) {
+            return (n < 16).then_some(ColorRole::Ansi(n));
+        }
+    }
+
+    match name.as_str() {
+        "background" | "bg" | "main_bg" | "background-color" | "background_color" | "base" => {
+            if in_selection {
+                Some(ColorRole::Selection)
+            } else if in_cursor {
+                None
+            } else {
+                Some(ColorRole::Background)
+            }
+        }
+        "foreground" | "fg" | "main_fg" | "text-color" | "text_color" | "text" | "font_color" => {
+            if in_selection || in_cursor {
+                None
+            } else {
+                Some(ColorRole::Foreground)
+            }
+        }
+        "cursor" | "cursor-color" | "cursor_color" => Some(ColorRole::Cursor),
+        "selection_background" | "selection-background" | "selection_bg" | "selected_bg" => {
+            Some(ColorRole::Selection)
+        }
+        "accent" | "accent_color" | "accent-color" | "hi_fg" | "selected-text" => {
+            Some(ColorRole::Accent)
+        }
+        "col.active_border" | "activebordercolor" | "active_border" | "border-color"
+        | "border_color" => Some(ColorRole::ActiveBorder),
+        "col.inactive_border" | "inactivebordercolor" | "inactive_border" => {
+            Some(ColorRole::InactiveBorder)
+        }
+        _ => None,
+    }
+}
+
+/// One distinct color of a theme and every `file: key` that uses it.
+#[derive(Clone, Debug)]
+pub struct PaletteEntry {
+    pub color: Color32,
+    pub keys: Vec<String>,
+}
+
+/// Resolved colors of a theme, looked up by file and key or by role.
+pub struct ThemeColors {
+    pub colors: Vec<KeyedColor>,
+}
+
+impl ThemeColors {
+    pub fn new(colors: Vec<KeyedColor>) -> Self {
+        Self { colors }
+    }
+
+    pub fn from_cache(file_cache: &HashMap<String, String>) -> Self {
+        Self::new(theme_keyed_colors(file_cache))
+    }
+
+    /// First color whose file name contains `file_hint` and whose key name is
+    /// one of `names`. Earlier names win.
+    pub fn find(&self, file_hint: &str, names: &[&str]) -> Option<Color32> {
+        self.find_keyed(file_hint, names).map(|c| c.color.value)
+    }
+
+    pub fn find_keyed(&self, file_hint: &str, names: &[&str]) -> Option<&KeyedColor> {
+        names.iter().find_map(|wanted| {
+            self.colors
+                .iter()
+                .filter(|c| c.file.to_lowercase().contains(file_hint))
+                .find(|c| key_name(&c.key) == *wanted)
+        })
+    }
+
+    /// The same colors as seen with the given color vision deficiency.
+    pub fn simulated(&self, mode: CvdMode) -> Self {
+        let mut colors = self.colors.clone();
+        for c in &mut colors {
+            c.color.value = simulate(c.color.value, mode);
+        }
+        Self::new(colors)
+    }
+
+    /// Distinct opaque colors in order of first appearance.
+    pub fn palette(&self) -> Vec<PaletteEntry> {
+        let mut palette: Vec<PaletteEntry> = Vec::new();
+        for c in &self.colors {
+            let opaque = opaque(c.color.value);
+            let key = format!("{}: {}", c.file, c.key);
+            match palette.iter_mut().find(|e| e.color == opaque) {
+                Some(entry) => entry.keys.push(key),
+                None => palette.push(PaletteEntry {
+                    color: opaque,
+                    keys: vec![key],
+                }),
+            }
+        }
+        palette
+    }
+
+    /// First color with `role`, preferring files whose name contains one of
+    /// `file_hints` in the given order.
+    pub fn role(&self, role: ColorRole, file_hints: &[&str]) -> Option<Color32> {
+        self.role_keyed(role, file_hints).map(|c| c.color.value)
+    }
+
+    pub fn role_keyed(&self, role: ColorRole, file_hints: &[&str]) -> Option<&KeyedColor> {
+        let with_role = |c: &&KeyedColor| role_for_key(&c.key) == Some(role);
+        file_hints
+            .iter()
+            .find_map(|hint| {
+                self.colors
+                    .iter()
+                    .filter(|c| c.file.to_lowercase().contains(hint))
+                    .find(with_role)
+            })
+            .or_else(|| self.colors.iter().find(with_role))
+    }
+}
+
+/// How colors are matched to the slots of an imported palette.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemapMode {
+    /// Each color takes the slot of the theme's own anchor color it is closest to.
+    Nearest,
+    /// Colors with a known role (background, ANSI red, …) take that role's
+    /// slot, everything else falls back to the nearest anchor.
+    SemanticKey,
+}
+
+/// Recolors `file_cache` with `target(role)` for each role, keeping every
+/// color's format and alpha. Anchors are the theme's first color for each
+/// role the target knows about.
+pub fn remap_by_role(
+    file_cache: &HashMap<String, String>,
+    mode: RemapMode,
+    target: impl Fn(ColorRole) -> Option<Color32>,
+) -> HashMap<String, String> {
+    let mut anchors: Vec<(Color32, Color32)> = Vec::new();
+    let mut seen: Vec<ColorRole> = Vec::new();
+    for c in theme_keyed_colors(file_cache) {
+        let Some(role) = role_for_key(&c.key) else {
+            continue;
+        };
+        if let Some(new_color) = target(role).filter(|_| !seen.contains(&role)) {
+            seen.push(role);
+            anchors.push((c.color.value, new_color));
+        }
+    }
+    let nearest = |color: Color32| {
+        anchors
+            .iter()
+            .min_by(|a, b| delta_e(color, a.0).total_cmp(&delta_e(color, b.0)))
+            .map(|(_, new_color)| *new_color)
+    };
+
+    file_cache
+        .iter()
+        .map(|(file, content)| {
+            let roles: HashMap<(usize, usize), ColorRole> = keyed_colors(file, content)
+                .into_iter()
+                .filter_map(|c| {
+                    let role = role_for_key(&c.key)?;
+                    Some(((c.color.line, c.color.start_col), role))
+                })
+                .collect();
+            let new_content = map_colors_in_content(content, |detected| {
+                let role = roles.get(&(detected.line, detected.start_col));
+                let new_color = match (mode, role) {
+                    (RemapMode::SemanticKey, Some(role)) => target(*role),
+                    _ => None,
+                }
+                .or_else(|| nearest(detected.value))?;
+                let [r, g, b, _] = new_color.to_srgba_unmultiplied();
+                let alpha = detected.value.to_srgba_unmultiplied()[3];
+                let color = Color32::from_rgba_unmultiplied(r, g, b, alpha);
+                (color != detected.value).then(|| format_like(color, &detected.hex_text))
+            });
+            (file.clone(), new_content)
+        })
+        .collect()
+}
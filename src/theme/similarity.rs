@@ -0,0 +1,125 @@
+use std::cmp::{Ordering, Reverse};
+
+use egui::Color32;
+
+use crate::theme::semantic::{ColorRole, ThemeColors};
+use crate::utils::colorspace::{delta_e, to_oklch};
+
+/// Number of most used colors kept in a signature.
+const SIGNATURE_COLORS: usize = 12;
+
+/// Below this OKLCH chroma a theme's accent counts as gray.
+const GRAY_CHROMA: f32 = 0.03;
+
+/// The colors that characterize a theme, for comparing it with others.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PaletteSignature {
+    pub background: Option<Color32>,
+    pub foreground: Option<Color32>,
+    /// Most used distinct colors with their share of all color keys
+    pub colors: Vec<(Color32, f32)>,
+}
+
+impl PaletteSignature {
+    pub fn new(colors: &ThemeColors) -> Self {
+        let hints = ["alacritty", "kitty", "ghostty"];
+        let mut palette = colors.palette();
+        // Stable sort keeps first appearance order between equally used colors
+        palette.sort_by_key(|e| Reverse(e.keys.len()));
+        palette.truncate(SIGNATURE_COLORS);
+        let total: usize = palette.iter().map(|e| e.keys.len()).sum();
+
+        Self {
+            background: colors.role(ColorRole::Background, &hints),
+            foreground: colors.role(ColorRole::Foreground, &hints),
+            colors: palette
+                .into_iter()
+                .map(|e| (e.color, e.keys.len() as f32 / total.max(1) as f32))
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    /// Perceptual distance to `other`: CIEDE2000 between the backgrounds,
+    /// the foregrounds and the palettes, each palette color matched with the
+    /// closest color of the other palette. 0 for identical themes, `None`
+    /// if either theme has no colors.
+    pub fn distance(&self, other: &PaletteSignature) -> Option<f32> {
+        if self.is_empty() || other.is_empty() {
+            return None;
+        }
+        let palette = (matched_distance(&self.colors, &other.colors)
+            + matched_distance(&other.colors, &self.colors))
+            / 2.0;
+
+        let mut parts = vec![(palette, 0.5)];
+        if let (Some(a), Some(b)) = (self.background, other.background) {
+            parts.push((delta_e(a, b), 0.35));
+        }
+        if let (Some(a), Some(b)) = (self.foreground, other.foreground) {
+            parts.push((delta_e(a, b), 0.15));
+        }
+        let weight: f32 = parts.iter().map(|(_, w)| w).sum();
+        Some(parts.iter().map(|(d, w)| d * w).sum::<f32>() / weight)
+    }
+
+    /// The most colorful of the main colors, usually the accent.
+    fn accent(&self) -> Option<Color32> {
+        self.colors
+            .iter()
+            .map(|(c, _)| *c)
+            .max_by(|a, b| to_oklch(*a)[1].total_cmp(&to_oklch(*b)[1]))
+    }
+
+    /// Orders themes for "sort by color": dark backgrounds before light ones,
+    /// then around the hue wheel by accent color with gray themes last, then
+    /// by background lightness.
+    pub fn color_order(&self, other: &PaletteSignature) -> Ordering {
+        self.color_key()
+            .iter()
+            .zip(other.color_key().iter())
+            .map(|(a, b)| a.total_cmp(b))
+            .find(|o| o.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+
+    fn color_key(&self) -> [f32; 3] {
+        let lightness = self.background.map(|c| to_oklch(c)[0]).unwrap_or(0.0);
+        let light = if lightness > 0.5 { 1.0 } else { 0.0 };
+        let hue = match self.accent().map(to_oklch) {
+            Some([_, chroma, hue]) if chroma >= GRAY_CHROMA => hue,
+            _ => 360.0,
+        };
+        [light, hue, lightness]
+    }
+}
+
+/// Weighted mean ΔE from each color of `from` to its closest color in `to`.
+fn matched_distance(from: &[(Color32, f32)], to: &[(Color32, f32)]) -> f32 {
+    from.iter()
+        .map(|(a, weight)| {
+            let closest = to
+                .iter()
+                .map(|(b, _)| delta_e(*a, *b))
+                .fold(f32::INFINITY, f32::min);
+            closest * weight
+        })
+        .sum()
+}
+
+/// Themes ordered from most to least similar to `target`, with distances.
+/// Themes without colors are left out.
+pub fn rank_similar<'a>(
+    target: &PaletteSignature,
+    others: impl IntoIterator<Item = (&'a String, &'a PaletteSignature)>,
+) -> Vec<(String, f32)> {
+    let mut ranked: Vec<(String, f32)> = others
+        .into_iter()
+        .filter_map(|(name, sig)| Some((name.clone(), target.distance(sig)?)))
+        .collect();
+    ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+    ranked
+}
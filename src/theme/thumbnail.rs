@@ -0,0 +1,193 @@
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use egui::{Color32, ColorImage};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{color_from_hex, color_to_hex};
+use crate::theme::scanner::{get_extension, load_theme_cache, scan_theme_files};
+use crate::theme::semantic::ThemeColors;
+use crate::theme::similarity::PaletteSignature;
+use crate::utils::path::expand_tilde;
+
+/// Largest size of a wallpaper thumbnail, aspect ratio is kept.
+const THUMB_WIDTH: u32 = 96;
+const THUMB_HEIGHT: u32 = 54;
+
+const WALLPAPER_EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+/// Main colors and wallpaper preview shown next to a theme name.
+#[derive(Clone, Default)]
+pub struct ThemeThumbnail {
+    pub palette: Vec<Color32>,
+    pub wallpaper: Option<ColorImage>,
+}
+
+/// What is stored in `<cache>/thumbnails/<theme>.json`, the wallpaper
+/// thumbnail sits next to it as `.png`.
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    /// Newest modification time of the theme folder and its files, in ms
+    modified: u64,
+    /// Sorted files of the theme, a file removed from a subfolder only
+    /// changes that subfolder's time
+    files: Vec<String>,
+    palette: Vec<String>,
+    has_wallpaper: bool,
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|d| d.join("omarchy-theme-maker").join("thumbnails"))
+}
+
+/// Cache file stem for a theme folder, unique per full path.
+fn cache_stem(theme_dir: &Path) -> String {
+    theme_dir
+        .to_string_lossy()
+        .trim_start_matches('/')
+        .replace('/', "%")
+}
+
+fn modified_ms(path: &Path) -> u64 {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// The first image in `backgrounds/`, where Omarchy themes keep wallpapers.
+fn find_wallpaper(files: &[String]) -> Option<&String> {
+    let mut images: Vec<&String> = files
+        .iter()
+        .filter(|f| f.starts_with("backgrounds/"))
+        .filter(|f| WALLPAPER_EXTENSIONS.contains(&get_extension(f).as_str()))
+        .collect();
+    images.sort();
+    images.into_iter().next()
+}
+
+fn read_wallpaper(path: &Path) -> Option<ColorImage> {
+    let image = image::open(path)
+        .map_err(|e| eprintln!("[DEBUG] Cannot read wallpaper {:?}: {}", path, e))
+        .ok()?
+        .thumbnail(THUMB_WIDTH, THUMB_HEIGHT)
+        .to_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    Some(ColorImage::from_rgba_unmultiplied(size, image.as_raw()))
+}
+
+fn write_png(path: &Path, image: &ColorImage) -> Result<(), String> {
+    let rgba: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|c| c.to_srgba_unmultiplied())
+        .collect();
+    image::save_buffer(
+        path,
+        &rgba,
+        image.size[0] as u32,
+        image.size[1] as u32,
+        image::ColorType::Rgba8,
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn read_cached(dir: &Path, stem: &str, modified: u64, files: &[String]) -> Option<ThemeThumbnail> {
+    let json = std::fs::read_to_string(dir.join(format!("{}.json", stem))).ok()?;
+    let entry: CacheEntry = serde_json::from_str(&json).ok()?;
+    if entry.modified != modified || entry.files != files {
+        return None;
+    }
+    let wallpaper = if entry.has_wallpaper {
+        Some(read_wallpaper(&dir.join(format!("{}.png", stem)))?)
+    } else {
+        None
+    };
+    Some(ThemeThumbnail {
+        palette: entry.palette.iter().map(|h| color_from_hex(h)).collect(),
+        wallpaper,
+    })
+}
+
+fn write_cached(dir: &Path, stem: &str, modified: u64, files: &[String], thumb: &ThemeThumbnail) {
+    let entry = CacheEntry {
+        modified,
+        files: files.to_vec(),
+        palette: thumb.palette.iter().map(|c| color_to_hex(*c)).collect(),
+        has_wallpaper: thumb.wallpaper.is_some(),
+    };
+    let result = std::fs::create_dir_all(dir)
+        .map_err(|e| e.to_string())
+        .and_then(|_| match &thumb.wallpaper {
+            Some(image) => write_png(&dir.join(format!("{}.png", stem)), image),
+            None => Ok(()),
+        })
+        .and_then(|_| serde_json::to_string(&entry).map_err(|e| e.to_string()))
+        .and_then(|json| {
+            std::fs::write(dir.join(format!("{}.json", stem)), json).map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        eprintln!("[DEBUG] Cannot write thumbnail cache for {}: {}", stem, e);
+    }
+}
+
+/// Palette strip and wallpaper thumbnail of the theme at `theme_path`, read
+/// from the disk cache unless a file in the theme changed, was added or was
+/// removed since it was made.
+pub fn load_thumbnail(theme_path: &str) -> ThemeThumbnail {
+    let theme_dir = expand_tilde(theme_path);
+    let mut files = scan_theme_files(theme_path);
+    files.sort();
+    let modified = files
+        .iter()
+        .map(|f| modified_ms(&theme_dir.join(f)))
+        .fold(modified_ms(&theme_dir), u64::max);
+
+    let cache = cache_dir();
+    let stem = cache_stem(&theme_dir);
+    if let Some(thumb) = cache
+        .as_deref()
+        .and_then(|dir| read_cached(dir, &stem, modified, &files))
+    {
+        return thumb;
+    }
+
+    eprintln!("[DEBUG] Building thumbnail for {}", theme_path);
+    let colors = ThemeColors::from_cache(&load_theme_cache(theme_path));
+    let thumb = ThemeThumbnail {
+        palette: PaletteSignature::new(&colors)
+            .colors
+            .into_iter()
+            .map(|(c, _)| c)
+            .collect(),
+        wallpaper: find_wallpaper(&files).and_then(|f| read_wallpaper(&theme_dir.join(f))),
+    };
+    if let Some(dir) = &cache {
+        write_cached(dir, &stem, modified, &files, &thumb);
+    }
+    thumb
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_misses_when_a_file_is_removed() {
+        let dir = std::env::temp_dir().join(format!("omarchy-thumb-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let files = vec!["backgrounds/1.png".to_string(), "colors.toml".to_string()];
+        let thumb = ThemeThumbnail {
+            palette: vec![Color32::from_rgb(0x89, 0xb4, 0xfa)],
+            wallpaper: None,
+        };
+        write_cached(&dir, "nord", 42, &files, &thumb);
+
+        let cached = read_cached(&dir, "nord", 42, &files).unwrap();
+        assert_eq!(cached.palette, thumb.palette);
+        assert!(read_cached(&dir, "nord", 42, &files[1..]).is_none());
+        assert!(read_cached(&dir, "nord", 43, &files).is_none());
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+
+use egui::Color32;
+
+use crate::theme::contrast::check_theme_contrast;
+use crate::theme::semantic::{keyed_colors, ColorRole, ThemeColors};
+use crate::utils::color::{contrast_ratio, format_like, map_colors_in_content, opaque};
+use crate::utils::colorspace::{from_oklch, to_oklch};
+
+/// OKLCH lightness the dark background and foreground are moved to.
+const LIGHT_BACKGROUND: f32 = 0.96;
+const LIGHT_FOREGROUND: f32 = 0.40;
+
+/// Fallback lightness of a typical dark theme's background and foreground.
+const DARK_BACKGROUND: f32 = 0.22;
+const DARK_FOREGROUND: f32 = 0.87;
+
+/// Passes over all contrast pairs before giving up on reaching the targets.
+const MAX_CONTRAST_PASSES: usize = 8;
+
+/// Color mapping turning a dark theme into a light one.
+pub struct LightVariant {
+    /// Lightness mirror applied to every color
+    map: HashMap<Color32, Color32>,
+    /// Contrast fixes of text colors, only for the file and key of the pair
+    /// that needed them
+    overrides: HashMap<(String, String), Color32>,
+}
+
+fn with_lightness(color: Color32, lightness: f32) -> Color32 {
+    let [_, c, h] = to_oklch(color);
+    from_oklch([lightness, c, h], color)
+}
+
+/// Moves `fg` away from the lightness of `bg` in small steps until the pair
+/// reaches `target`, or lightness runs out.
+fn raise_contrast(fg: Color32, bg: Color32, target: f32) -> Color32 {
+    let step = if to_oklch(bg)[0] > 0.5 { -0.01 } else { 0.01 };
+    let mut l = to_oklch(fg)[0];
+    let mut adjusted = fg;
+    while contrast_ratio(adjusted, bg) < target && (0.0..=1.0).contains(&(l + step)) {
+        l += step;
+        adjusted = with_lightness(fg, l);
+    }
+    adjusted
+}
+
+impl LightVariant {
+    /// Mirrors lightness around the theme's own background/foreground so the
+    /// background becomes near white and text dark, keeping every hue. Text
+    /// colors are then darkened until they reach the contrast they had
+    /// against their background in the dark theme (capped at WCAG AAA).
+    /// Those fixes apply to the key of the pair only, so the same color used
+    /// as a border elsewhere keeps the plain mapping.
+    pub fn generate(colors: &ThemeColors) -> Self {
+        let lightness_of = |role| {
+            colors
+                .role(role, &["alacritty", "kitty", "ghostty"])
+                .map(|c| to_oklch(c)[0])
+        };
+        let dark_bg = lightness_of(ColorRole::Background).unwrap_or(DARK_BACKGROUND);
+        let dark_fg = lightness_of(ColorRole::Foreground).unwrap_or(DARK_FOREGROUND);
+        let span = if (dark_fg - dark_bg).abs() < 0.1 {
+            DARK_FOREGROUND - DARK_BACKGROUND
+        } else {
+            dark_fg - dark_bg
+        };
+        let slope = (LIGHT_FOREGROUND - LIGHT_BACKGROUND) / span;
+
+        let mut map = HashMap::new();
+        for entry in colors.palette() {
+            let l = to_oklch(entry.color)[0];
+            let new_l = (LIGHT_BACKGROUND + slope * (l - dark_bg)).clamp(0.05, 0.99);
+            map.insert(entry.color, with_lightness(entry.color, new_l));
+        }
+        let mut variant = Self {
+            map,
+            overrides: HashMap::new(),
+        };
+
+        let targets: Vec<f32> = check_theme_contrast(colors)
+            .iter()
+            .map(|pair| pair.ratio.min(if pair.required { 7.0 } else { 4.5 }))
+            .collect();
+
+        // A fix can lower another pair sharing a key, so re-check every pair
+        // until none changes
+        for _ in 0..MAX_CONTRAST_PASSES {
+            let light = variant.apply_to_colors(colors);
+            let mut changed = false;
+            for (pair, target) in check_theme_contrast(&light).iter().zip(&targets) {
+                if pair.ratio >= *target {
+                    continue;
+                }
+                let fg = opaque(pair.foreground);
+                let adjusted = raise_contrast(fg, opaque(pair.background), *target);
+                if adjusted != fg {
+                    variant
+                        .overrides
+                        .insert(pair.foreground_at.clone(), adjusted);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        variant
+    }
+
+    fn map_color(&self, color: Color32, file: &str, key: &str) -> Color32 {
+        let mapped = self
+            .overrides
+            .get(&(file.to_string(), key.to_string()))
+            .or_else(|| self.map.get(&opaque(color)));
+        match mapped {
+            Some(mapped) => {
+                let [r, g, b, _] = mapped.to_srgba_unmultiplied();
+                Color32::from_rgba_unmultiplied(r, g, b, color.to_srgba_unmultiplied()[3])
+            }
+            None => color,
+        }
+    }
+
+    fn apply_to_colors(&self, colors: &ThemeColors) -> ThemeColors {
+        let mut mapped = colors.colors.clone();
+        for c in &mut mapped {
+            c.color.value = self.map_color(c.color.value, &c.file, &c.key);
+        }
+        ThemeColors::new(mapped)
+    }
+
+    /// Recolors every cached file, keeping each color's format and alpha.
+    pub fn apply(&self, file_cache: &HashMap<String, String>) -> HashMap<String, String> {
+        file_cache
+            .iter()
+            .map(|(file, content)| {
+                let keys: HashMap<(usize, usize), String> = keyed_colors(file, content)
+                    .into_iter()
+                    .map(|c| ((c.color.line, c.color.start_col), c.key))
+                    .collect();
+                let new_content = map_colors_in_content(content, |detected| {
+                    let key = keys
+                        .get(&(detected.line, detected.start_col))
+                        .map_or("", |k| k.as_str());
+                    let new_color = self.map_color(detected.value, file, key);
+                    (new_color != detected.value)
+                        .then(|| format_like(new_color, &detected.hex_text))
+                });
+                (file.clone(), new_content)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::semantic::keyed_colors;
+
+    fn theme() -> HashMap<String, String> {
+        HashMap::from([
+            (
+                "alacritty.toml".to_string(),
+                "[colors.primary]\nbackground = \"#1e1e2e\"\nforeground = \"#cdd6f4\"\n"
+                    .to_string(),
+            ),
+            (
+                "waybar.css".to_string(),
+                "@define-color background #1e1e2e;\n@define-color foreground #89b4fa;\n"
+                    .to_string(),
+            ),
+            (
+                "hyprland.conf".to_string(),
+                "general {\n    col.active_border = rgb(89b4fa)\n}\n".to_string(),
+            ),
+        ])
+    }
+
+    fn color_of(cache: &HashMap<String, String>, file: &str, key: &str) -> Color32 {
+        keyed_colors(file, &cache[file])
+            .into_iter()
+            .find(|c| c.key.ends_with(key))
+            .unwrap()
+            .color
+            .value
+    }
+
+    #[test]
+    fn contrast_fix_stays_on_the_paired_key() {
+        let cache = theme();
+        let variant = LightVariant::generate(&ThemeColors::from_cache(&cache));
+        let light = variant.apply(&cache);
+
+        let border = color_of(&light, "hyprland.conf", "active_border");
+        let bar_text = color_of(&light, "waybar.css", "foreground");
+        let blue = color_of(&cache, "hyprland.conf", "active_border");
+        assert_eq!(border, variant.map[&blue]);
+        assert_ne!(bar_text, border);
+    }
+
+    #[test]
+    fn every_pair_keeps_its_contrast() {
+        let cache = theme();
+        let dark = check_theme_contrast(&ThemeColors::from_cache(&cache));
+        let variant = LightVariant::generate(&ThemeColors::from_cache(&cache));
+        let light = check_theme_contrast(&ThemeColors::from_cache(&variant.apply(&cache)));
+
+        assert_eq!(dark.len(), light.len());
+        for (before, after) in dark.iter().zip(&light) {
+            let cap = if before.required { 7.0 } else { 4.5 };
+            assert!(
+                after.ratio >= before.ratio.min(cap),
+                "{} dropped from {:.1} to {:.1}",
+                before.label,
+                before.ratio,
+                after.ratio
+            );
+        }
+    }
+}
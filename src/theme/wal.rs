@@ -0,0 +1,80 @@
+use std::path::Path;
+
+use egui::Color32;
+use serde_json::Value;
+
+use crate::theme::semantic::ColorRole;
+use crate::utils::color::parse_hex_color;
+
+/// The `colors.json` written by pywal, and by wallust with its pywal template.
+#[derive(Clone, Debug)]
+pub struct WalColors {
+    pub background: Color32,
+    pub foreground: Color32,
+    pub cursor: Color32,
+    pub colors: [Color32; 16],
+    pub wallpaper: Option<String>,
+}
+
+fn hex_at(value: &Value, section: &str, key: &str) -> Result<Color32, String> {
+    let text = value
+        .get(section)
+        .and_then(|s| s.get(key))
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("Missing {}.{}", section, key))?;
+    parse_hex_color(text.trim())
+        .ok_or_else(|| format!("{}.{} is not a color: {}", section, key, text))
+}
+
+impl WalColors {
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let doc: Value =
+            serde_json::from_str(json).map_err(|e| format!("Invalid colors.json: {}", e))?;
+
+        let mut colors = [Color32::BLACK; 16];
+        for (n, color) in colors.iter_mut().enumerate() {
+            *color = hex_at(&doc, "colors", &format!("color{}", n))?;
+        }
+        let background = hex_at(&doc, "special", "background")?;
+        let foreground = hex_at(&doc, "special", "foreground")?;
+        let cursor = hex_at(&doc, "special", "cursor").unwrap_or(foreground);
+        let wallpaper = doc
+            .get("wallpaper")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+
+        Ok(Self {
+            background,
+            foreground,
+            cursor,
+            colors,
+            wallpaper,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::parse(&json)
+    }
+
+    /// The wal slot used for a role. Accents and borders follow the usual
+    /// pywal templates: color4 for active, color8 for inactive.
+    pub fn color_for_role(&self, role: ColorRole) -> Color32 {
+        match role {
+            ColorRole::Background => self.background,
+            ColorRole::Foreground => self.foreground,
+            ColorRole::Cursor => self.cursor,
+            ColorRole::Selection | ColorRole::InactiveBorder => self.colors[8],
+            ColorRole::Accent | ColorRole::ActiveBorder => self.colors[4],
+            ColorRole::Ansi(n) => self.colors[n as usize % 16],
+        }
+    }
+
+    /// Special colors followed by color0-color15, for previews.
+    pub fn swatches(&self) -> Vec<Color32> {
+        let mut swatches = vec![self.background, self.foreground, self.cursor];
+        swatches.extend(self.colors);
+        swatches
+    }
+}
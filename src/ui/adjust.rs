@@ -0,0 +1,114 @@
+use crate::app::OmarchyApp;
+use crate::theme::adjust::ColorAdjustment;
+use crate::theme::semantic::ThemeColors;
+use eframe::egui::{self, Color32, Sense};
+
+/// Draws a row of swatches filling the available width.
+pub fn palette_strip(ui: &mut egui::Ui, colors: &[Color32], height: f32) {
+    let (rect, _) =
+        ui.allocate_exact_size(egui::vec2(ui.available_width(), height), Sense::hover());
+    if colors.is_empty() {
+        return;
+    }
+    let width = rect.width() / colors.len() as f32;
+    for (i, color) in colors.iter().enumerate() {
+        let min = egui::pos2(rect.left() + i as f32 * width, rect.top());
+        ui.painter().rect_filled(
+            egui::Rect::from_min_size(min, egui::vec2(width, height)),
+            0.0,
+            *color,
+        );
+    }
+}
+
+pub fn ui_adjust_window(ctx: &egui::Context, app: &mut OmarchyApp) {
+    if !app.show_adjust {
+        return;
+    }
+
+    let before: Vec<Color32> = ThemeColors::from_cache(&app.file_cache)
+        .palette()
+        .iter()
+        .map(|e| e.color)
+        .collect();
+
+    let mut open = true;
+    let mut apply = false;
+    let mut light_variant = false;
+    egui::Window::new("Adjust theme colors")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            let adj = &mut app.adjustment;
+            egui::Grid::new("adjust_grid")
+                .num_columns(2)
+                .spacing([12.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label("Hue shift");
+                    ui.add(egui::Slider::new(&mut adj.hue_shift, -180.0..=180.0).suffix("°"));
+                    ui.end_row();
+
+                    ui.label("Saturation");
+                    ui.add(egui::Slider::new(&mut adj.saturation, 0.0..=2.0).suffix("×"));
+                    ui.end_row();
+
+                    ui.label("Lightness");
+                    ui.add(egui::Slider::new(&mut adj.lightness, 0.5..=1.5).suffix("×"));
+                    ui.end_row();
+
+                    ui.label("Temperature");
+                    ui.add(egui::Slider::new(&mut adj.temperature, -1.0..=1.0).text("cool / warm"));
+                    ui.end_row();
+
+                    ui.label("Light/dark");
+                    ui.checkbox(&mut adj.invert_lightness, "Invert lightness");
+                    ui.end_row();
+                });
+
+            ui.separator();
+            let after: Vec<Color32> = before.iter().map(|c| adj.apply(*c)).collect();
+            ui.label("Before");
+            palette_strip(ui, &before, 18.0);
+            ui.label("After");
+            palette_strip(ui, &after, 18.0);
+            ui.label(
+                egui::RichText::new(
+                    "The preview panel shows the result until you close this window",
+                )
+                .small()
+                .color(Color32::GRAY),
+            );
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Reset").clicked() {
+                    *adj = ColorAdjustment::default();
+                }
+                if ui
+                    .add_enabled(!adj.is_identity(), egui::Button::new("Apply"))
+                    .clicked()
+                {
+                    apply = true;
+                }
+            });
+
+            ui.separator();
+            ui.label("Make a light companion of this dark theme, keeping hues and text contrast:");
+            if ui.button("Generate light variant…").clicked() {
+                light_variant = true;
+            }
+        });
+
+    if apply {
+        app.apply_adjustment();
+    }
+    if light_variant {
+        app.generate_light_variant();
+    }
+    if !open {
+        app.show_adjust = false;
+        app.adjustment = ColorAdjustment::default();
+    }
+}
@@ -1,5 +1,8 @@
 use crate::app::OmarchyApp;
+use crate::theme::search::ColorQuery;
+use crate::theme::semantic::keyed_colors;
 use crate::utils::color::get_contrast_color;
+use crate::utils::cvd::simulate;
 use eframe::egui;
 
 pub fn ui_colors_panel(ctx: &egui::Context, app: &mut OmarchyApp) {
@@ -21,19 +24,14 @@ pub fn ui_colors_panel(ctx: &egui::Context, app: &mut OmarchyApp) {
         let file_content = app.file_content.clone();
         let file_index = app.selected_file_index;
         let theme_files = app.theme_files.clone();
-        let themes_path = app.themes_path.clone();
         let theme_index = app.selected_theme_index;
         let theme_names = app.theme_names.clone();
+        let cvd_mode = app.cvd_mode;
 
         let file_path = if let (Some(ti), Some(fi)) = (theme_index, file_index) {
             if let (Some(theme_name), Some(file_name)) = (theme_names.get(ti), theme_files.get(fi))
             {
-                Some(format!(
-                    "{}/{}/{}",
-                    themes_path.trim_end_matches('/'),
-                    theme_name,
-                    file_name
-                ))
+                Some(format!("{}/{}", app.theme_path(theme_name), file_name))
             } else {
                 None
             }
@@ -67,6 +65,31 @@ pub fn ui_colors_panel(ctx: &egui::Context, app: &mut OmarchyApp) {
             ui.separator();
         }
 
+        ui.add(
+            egui::TextEdit::singleline(&mut app.color_filter)
+                .hint_text("Filter colors: key, #89b4fa or #89b4fa~5 (ΔE)")
+                .desired_width(f32::INFINITY),
+        );
+        let query = ColorQuery::parse(&app.color_filter);
+        if query.is_none() && !app.color_filter.trim().is_empty() {
+            ui.weak("Invalid ΔE, use e.g. #89b4fa~5");
+        }
+        // Ids of the colors matching the filter, `None` when not filtering
+        let matching_ids: Option<std::collections::HashSet<String>> = query.map(|query| {
+            let file_name = file_index
+                .and_then(|i| theme_files.get(i))
+                .cloned()
+                .unwrap_or_default();
+            keyed_colors(&file_name, &file_content)
+                .into_iter()
+                .filter(|c| query.matches(&c.key, &c.color))
+                .map(|c| c.color.id)
+                .collect()
+        });
+        if matching_ids.as_ref().is_some_and(|ids| ids.is_empty()) {
+            ui.weak("No colors match");
+        }
+
         egui::ScrollArea::vertical().show(ui, |ui| {
             let line_height = 20.0;
             let line_number_width = 50.0;
@@ -79,14 +102,20 @@ pub fn ui_colors_panel(ctx: &egui::Context, app: &mut OmarchyApp) {
                     .iter()
                     .filter(|c| c.line == line_idx)
                     .collect();
+                if let Some(ids) = &matching_ids {
+                    if !line_colors.iter().any(|c| ids.contains(&c.id)) {
+                        continue;
+                    }
+                }
 
                 let get_effective_color =
                     |color_info: &&crate::utils::color::DetectedColor| -> egui::Color32 {
-                        if let Some(new_hex) = modified_colors.get(&color_info.id) {
+                        let color = if let Some(new_hex) = modified_colors.get(&color_info.id) {
                             crate::config::color_from_hex(new_hex)
                         } else {
                             color_info.value
-                        }
+                        };
+                        simulate(color, cvd_mode)
                     };
 
                 let line_color = line_colors.first().map(get_effective_color);
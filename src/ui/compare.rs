@@ -0,0 +1,108 @@
+use crate::app::OmarchyApp;
+use crate::config::color_to_hex;
+use crate::theme::compare::{FileComparison, ThemeComparison};
+use crate::ui::adjust::palette_strip;
+use crate::ui::preview::swatch;
+use crate::utils::color::{color_to_format, ColorFormat};
+use eframe::egui::{self, Color32};
+
+fn color_cell(ui: &mut egui::Ui, color: Option<Color32>) {
+    match color {
+        Some(color) => {
+            ui.horizontal(|ui| {
+                swatch(ui, color);
+                if color.a() == 255 {
+                    ui.monospace(color_to_hex(color));
+                } else {
+                    ui.monospace(color_to_format(color, &ColorFormat::Hex8));
+                }
+            });
+        }
+        None => {
+            ui.weak("—");
+        }
+    }
+}
+
+fn file_list(ui: &mut egui::Ui, title: &str, files: &[String]) {
+    if files.is_empty() {
+        return;
+    }
+    ui.label(egui::RichText::new(title).strong());
+    for file in files {
+        ui.monospace(file);
+    }
+    ui.add_space(6.0);
+}
+
+fn file_section(ui: &mut egui::Ui, cmp: &ThemeComparison, file: &FileComparison) {
+    let status = if file.identical {
+        "identical".to_string()
+    } else if file.key_diffs.is_empty() {
+        "differs, same colors".to_string()
+    } else {
+        format!("{} colors differ", file.key_diffs.len())
+    };
+    let header = format!("{} ({})", file.file, status);
+    if file.key_diffs.is_empty() {
+        ui.add_enabled(false, egui::Label::new(header));
+        return;
+    }
+
+    egui::CollapsingHeader::new(header)
+        .id_salt(("compare_file", &file.file))
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::Grid::new(("compare_keys", &file.file))
+                .num_columns(3)
+                .striped(true)
+                .spacing([12.0, 4.0])
+                .show(ui, |ui| {
+                    ui.weak("Key");
+                    ui.weak(&cmp.left);
+                    ui.weak(&cmp.right);
+                    ui.end_row();
+                    for diff in &file.key_diffs {
+                        ui.monospace(&diff.key);
+                        color_cell(ui, diff.left);
+                        color_cell(ui, diff.right);
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+pub fn ui_compare_window(ctx: &egui::Context, app: &mut OmarchyApp) {
+    let Some(cmp) = &app.compare else {
+        return;
+    };
+
+    let mut open = true;
+    egui::Window::new(format!("Compare {} ↔ {}", cmp.left, cmp.right))
+        .open(&mut open)
+        .default_size([620.0, 480.0])
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.heading("Palette");
+                ui.label(format!("Only in {}:", cmp.left));
+                palette_strip(ui, &cmp.palette_only_left, 18.0);
+                ui.label(format!("Only in {}:", cmp.right));
+                palette_strip(ui, &cmp.palette_only_right, 18.0);
+                if cmp.palette_only_left.is_empty() && cmp.palette_only_right.is_empty() {
+                    ui.label("Both themes use the same colors");
+                }
+
+                ui.separator();
+                ui.heading("Files");
+                file_list(ui, &format!("Only in {}", cmp.left), &cmp.only_left);
+                file_list(ui, &format!("Only in {}", cmp.right), &cmp.only_right);
+                for file in &cmp.files {
+                    file_section(ui, cmp, file);
+                }
+            });
+        });
+
+    if !open {
+        app.compare = None;
+    }
+}
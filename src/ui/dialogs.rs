@@ -0,0 +1,563 @@
+use crate::app::{OmarchyApp, ThemeDialog};
+use crate::theme::apply::APPLY_TIMEOUT;
+use crate::theme::archive::{inspect_archive, ArchiveInfo};
+use crate::theme::base16::Base16Scheme;
+use crate::theme::export::PaletteFormat;
+use crate::theme::git::{theme_name_from_url, GitJobKind};
+use crate::theme::ops::{theme_exists, unique_theme_name, validate_theme_name};
+use crate::theme::semantic::RemapMode;
+use crate::theme::wal::WalColors;
+use crate::ui::adjust::palette_strip;
+use eframe::egui;
+
+/// Shows the name field of a rename/duplicate dialog with collision feedback.
+/// Returns true if the entered name can be used.
+fn theme_name_field(ui: &mut egui::Ui, roots: &[String], new_name: &mut String) -> bool {
+    ui.add(egui::TextEdit::singleline(new_name).desired_width(250.0));
+
+    if let Err(e) = validate_theme_name(new_name) {
+        ui.colored_label(egui::Color32::YELLOW, e);
+        return false;
+    }
+    if theme_exists(roots, new_name.trim()) {
+        let suggestion = unique_theme_name(roots, new_name);
+        ui.horizontal(|ui| {
+            ui.colored_label(egui::Color32::YELLOW, "Name already taken.");
+            if ui.link(format!("Use \"{}\"", suggestion)).clicked() {
+                *new_name = suggestion.clone();
+            }
+        });
+        return false;
+    }
+    true
+}
+
+/// New theme name and, with several writable theme folders, which one to
+/// save into.
+fn save_as_fields(
+    ui: &mut egui::Ui,
+    app: &OmarchyApp,
+    theme: &str,
+    new_name: &mut String,
+    root: &mut String,
+) -> bool {
+    ui.label(format!("Save \"{}\" with your changes as:", theme));
+    let name_ok = theme_name_field(ui, &app.root_paths(), new_name);
+    root_field(ui, app, root) && name_ok
+}
+
+/// With several writable theme folders, which one a new theme goes into.
+/// Returns false when there is none.
+fn root_field(ui: &mut egui::Ui, app: &OmarchyApp, root: &mut String) -> bool {
+    let writable = app.writable_roots();
+    if writable.len() > 1 {
+        ui.label("Into:");
+        egui::ComboBox::from_id_salt("save_root")
+            .selected_text(root.as_str())
+            .width(350.0)
+            .show_ui(ui, |ui| {
+                for path in writable {
+                    ui.selectable_value(root, path.clone(), path);
+                }
+            });
+    } else if writable.is_empty() {
+        ui.colored_label(
+            egui::Color32::YELLOW,
+            "No writable theme folder, check the paths in Settings.",
+        );
+        return false;
+    }
+    true
+}
+
+/// Archive path, what it contains and the name to unpack it as. The archive
+/// is only re-read when the path changes.
+fn import_archive_fields(
+    ui: &mut egui::Ui,
+    app: &OmarchyApp,
+    path: &mut String,
+    new_name: &mut String,
+    root: &mut String,
+    info: &mut Option<Result<ArchiveInfo, String>>,
+) -> bool {
+    ui.label("Theme archive (.tar.gz):");
+    let path_edit = ui.add(egui::TextEdit::singleline(path).desired_width(350.0));
+    if path_edit.changed() {
+        *info = None;
+    }
+    if path.trim().is_empty() {
+        return false;
+    }
+
+    let checked = info.get_or_insert_with(|| {
+        let result = inspect_archive(&crate::utils::path::expand_tilde(path.trim()));
+        if let Ok(archive) = &result {
+            *new_name = archive.theme_name.clone();
+        }
+        result
+    });
+    match checked {
+        Ok(archive) => {
+            ui.label(format!("{} files", archive.files.len()));
+            ui.label("Theme name:");
+            let name_ok = theme_name_field(ui, &app.root_paths(), new_name);
+            root_field(ui, app, root) && name_ok
+        }
+        Err(e) => {
+            ui.colored_label(egui::Color32::YELLOW, e.as_str());
+            false
+        }
+    }
+}
+
+/// Scheme path, a preview of its slots and where to import it. Returns true
+/// once the scheme parses and the target is usable. The scheme is only
+/// re-read when the path changes.
+fn import_scheme_fields(
+    ui: &mut egui::Ui,
+    app: &OmarchyApp,
+    path: &mut String,
+    new_name: &mut String,
+    root: &mut String,
+    remap: &mut Option<RemapMode>,
+    scheme: &mut Option<Result<Base16Scheme, String>>,
+) -> bool {
+    ui.label("Scheme file (.yaml):");
+    let path_edit = ui.add(egui::TextEdit::singleline(path).desired_width(350.0));
+    if path_edit.changed() {
+        *scheme = None;
+    }
+
+    if path.trim().is_empty() {
+        return false;
+    }
+    let scheme = match scheme
+        .get_or_insert_with(|| Base16Scheme::load(&crate::utils::path::expand_tilde(path.trim())))
+    {
+        Ok(scheme) => scheme,
+        Err(e) => {
+            ui.colored_label(egui::Color32::YELLOW, e.as_str());
+            return false;
+        }
+    };
+
+    let kind = if scheme.is_base24() {
+        "base24"
+    } else {
+        "base16"
+    };
+    if scheme.author.is_empty() {
+        ui.label(format!("{} ({})", scheme.name, kind));
+    } else {
+        ui.label(format!("{} by {} ({})", scheme.name, scheme.author, kind));
+    }
+    palette_strip(ui, &scheme.slots, 18.0);
+    ui.separator();
+
+    let selected = app.selected_theme_name();
+    ui.radio_value(remap, None, "Create a new theme from templates");
+    ui.add_enabled_ui(selected.is_some(), |ui| {
+        let theme = selected.as_deref().unwrap_or("selected theme");
+        ui.radio_value(
+            remap,
+            Some(RemapMode::SemanticKey),
+            format!("Recolor \"{}\" by semantic key", theme),
+        );
+        ui.radio_value(
+            remap,
+            Some(RemapMode::Nearest),
+            format!("Recolor \"{}\" by nearest color", theme),
+        );
+    });
+
+    match remap {
+        None => {
+            if new_name.is_empty() {
+                *new_name = unique_theme_name(&app.root_paths(), &scheme.theme_name());
+            }
+            ui.label("Theme name:");
+            let name_ok = theme_name_field(ui, &app.root_paths(), new_name);
+            root_field(ui, app, root) && name_ok
+        }
+        Some(_) => {
+            ui.label(
+                egui::RichText::new("Changes stay in memory until saved and can be undone")
+                    .small()
+                    .weak(),
+            );
+            selected.is_some()
+        }
+    }
+}
+
+/// The colors.json path, its colors and how to map them onto the selected
+/// theme. Returns true once the file parses. The file is only re-read when
+/// the path changes.
+fn import_wal_fields(
+    ui: &mut egui::Ui,
+    app: &OmarchyApp,
+    path: &mut String,
+    mode: &mut RemapMode,
+    colors: &mut Option<Result<WalColors, String>>,
+) -> bool {
+    let theme = app.selected_theme_name().unwrap_or_default();
+    ui.label(format!("Recolor \"{}\" from colors.json:", theme));
+    let path_edit = ui.add(egui::TextEdit::singleline(path).desired_width(350.0));
+    if path_edit.changed() {
+        *colors = None;
+    }
+
+    let wal = match colors
+        .get_or_insert_with(|| WalColors::load(&crate::utils::path::expand_tilde(path.trim())))
+    {
+        Ok(wal) => wal,
+        Err(e) => {
+            ui.colored_label(egui::Color32::YELLOW, e.as_str());
+            return false;
+        }
+    };
+    if let Some(wallpaper) = &wal.wallpaper {
+        ui.label(egui::RichText::new(format!("Generated from {}", wallpaper)).small());
+    }
+    palette_strip(ui, &wal.swatches(), 18.0);
+    ui.separator();
+
+    ui.radio_value(mode, RemapMode::SemanticKey, "Match by semantic key");
+    ui.radio_value(mode, RemapMode::Nearest, "Match by nearest color");
+    ui.label(
+        egui::RichText::new("Changes stay in memory until saved and can be undone")
+            .small()
+            .weak(),
+    );
+    !theme.is_empty()
+}
+
+pub fn ui_theme_dialog(ctx: &egui::Context, app: &mut OmarchyApp) {
+    let Some(mut dialog) = app.theme_dialog.take() else {
+        return;
+    };
+
+    let title = match &dialog {
+        ThemeDialog::Rename { .. } => "Rename theme",
+        ThemeDialog::Duplicate { .. } => "Duplicate theme",
+        ThemeDialog::Delete { .. } => "Delete theme",
+        ThemeDialog::SaveAs { .. } => "Save as new theme",
+        ThemeDialog::ExportPalette { .. } => "Export palette",
+        ThemeDialog::ExportArchive { .. } => "Export theme archive",
+        ThemeDialog::ImportArchive { .. } => "Import theme archive",
+        ThemeDialog::ImportGit { .. } => "Install theme from git",
+        ThemeDialog::ImportScheme { .. } => "Import base16/base24 scheme",
+        ThemeDialog::ImportWal { .. } => "Import pywal/wallust colors",
+        ThemeDialog::EditTags { .. } => "Edit tags",
+    };
+
+    let mut keep_open = true;
+    let mut confirmed = false;
+
+    egui::Window::new(title)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .show(ctx, |ui| {
+            let can_confirm = match &mut dialog {
+                ThemeDialog::Rename { theme, new_name } => {
+                    ui.label(format!("New name for \"{}\":", theme));
+                    theme_name_field(ui, &app.root_paths(), new_name)
+                }
+                ThemeDialog::Duplicate { theme, new_name } => {
+                    ui.label(format!("Name for the copy of \"{}\":", theme));
+                    theme_name_field(ui, &app.root_paths(), new_name)
+                }
+                ThemeDialog::Delete { theme } => {
+                    ui.label(format!(
+                        "Move \"{}\" to {}/{}?",
+                        theme,
+                        app.theme_root_path(theme).trim_end_matches('/'),
+                        crate::theme::ops::TRASH_DIR
+                    ));
+                    if app.has_unsaved_changes
+                        && app.selected_theme_name().as_deref() == Some(theme.as_str())
+                    {
+                        ui.colored_label(egui::Color32::YELLOW, "Unsaved changes will be lost.");
+                    }
+                    true
+                }
+                ThemeDialog::SaveAs {
+                    theme,
+                    new_name,
+                    root,
+                } => save_as_fields(ui, app, theme, new_name, root),
+                ThemeDialog::ExportPalette {
+                    theme,
+                    format,
+                    path,
+                } => {
+                    ui.label(format!("Export the colors of \"{}\" as:", theme));
+                    let previous = *format;
+                    egui::ComboBox::from_id_salt("palette_format")
+                        .selected_text(format.label())
+                        .show_ui(ui, |ui| {
+                            for f in PaletteFormat::ALL {
+                                ui.selectable_value(format, f, f.label());
+                            }
+                        });
+                    if *format != previous {
+                        if let Some(stem) = path.strip_suffix(previous.extension()) {
+                            *path = format!("{}{}", stem, format.extension());
+                        }
+                    }
+                    ui.label("File:");
+                    ui.add(egui::TextEdit::singleline(path).desired_width(350.0));
+                    !path.trim().is_empty()
+                }
+                ThemeDialog::ExportArchive { theme, path } => {
+                    ui.label(format!("Pack \"{}\" with all its files into:", theme));
+                    ui.add(egui::TextEdit::singleline(path).desired_width(350.0));
+                    if app.has_unsaved_changes
+                        && app.selected_theme_name().as_deref() == Some(theme.as_str())
+                    {
+                        ui.colored_label(
+                            egui::Color32::YELLOW,
+                            "Unsaved changes are not included.",
+                        );
+                    }
+                    let exists = !path.trim().is_empty()
+                        && crate::utils::path::expand_tilde(path.trim()).exists();
+                    if exists {
+                        ui.colored_label(
+                            egui::Color32::YELLOW,
+                            "A file with this name already exists.",
+                        );
+                    }
+                    !path.trim().is_empty() && !exists
+                }
+                ThemeDialog::ImportArchive {
+                    path,
+                    new_name,
+                    root,
+                    info,
+                } => import_archive_fields(ui, app, path, new_name, root, info),
+                ThemeDialog::ImportGit {
+                    url,
+                    new_name,
+                    root,
+                } => {
+                    ui.label("Repository URL or local path:");
+                    let url_edit = ui.add(egui::TextEdit::singleline(url).desired_width(350.0));
+                    if url_edit.changed() {
+                        *new_name = theme_name_from_url(url);
+                    }
+                    ui.label("Theme name:");
+                    let name_ok = theme_name_field(ui, &app.root_paths(), new_name);
+                    root_field(ui, app, root) && name_ok && !url.trim().is_empty()
+                }
+                ThemeDialog::ImportScheme {
+                    path,
+                    new_name,
+                    root,
+                    remap,
+                    scheme,
+                } => import_scheme_fields(ui, app, path, new_name, root, remap, scheme),
+                ThemeDialog::ImportWal { path, mode, colors } => {
+                    import_wal_fields(ui, app, path, mode, colors)
+                }
+                ThemeDialog::EditTags { theme, tags } => {
+                    ui.label(format!("Tags of \"{}\", separated by commas:", theme));
+                    ui.add(
+                        egui::TextEdit::singleline(tags)
+                            .hint_text("dark, warm, work")
+                            .desired_width(350.0),
+                    );
+                    true
+                }
+            };
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Cancel").clicked() {
+                    keep_open = false;
+                }
+                let ok_label = match dialog {
+                    ThemeDialog::Delete { .. } => "Delete",
+                    ThemeDialog::SaveAs { .. } => "Save",
+                    ThemeDialog::ExportPalette { .. } => "Export",
+                    ThemeDialog::ExportArchive { .. } => "Export",
+                    ThemeDialog::ImportGit { .. } => "Clone",
+                    ThemeDialog::ImportArchive { .. }
+                    | ThemeDialog::ImportScheme { .. }
+                    | ThemeDialog::ImportWal { .. } => "Import",
+                    _ => "OK",
+                };
+                if ui
+                    .add_enabled(can_confirm, egui::Button::new(ok_label))
+                    .clicked()
+                {
+                    confirmed = true;
+                }
+            });
+        });
+
+    if confirmed {
+        let result = match &dialog {
+            ThemeDialog::Rename { theme, new_name } => app.rename_theme(theme, new_name),
+            ThemeDialog::Duplicate { theme, new_name } => app.duplicate_theme(theme, new_name),
+            ThemeDialog::Delete { theme } => app.delete_theme(theme),
+            ThemeDialog::SaveAs { new_name, root, .. } => app.save_as_new(new_name, root),
+            ThemeDialog::ExportPalette {
+                theme,
+                format,
+                path,
+            } => app.export_theme_palette(theme, *format, path),
+            ThemeDialog::ExportArchive { theme, path } => app.export_theme_archive(theme, path),
+            ThemeDialog::ImportGit {
+                url,
+                new_name,
+                root,
+            } => app.import_theme_from_git(url, new_name, root),
+            ThemeDialog::ImportArchive {
+                path,
+                new_name,
+                root,
+                ..
+            } => app.import_theme_archive(path, new_name, root),
+            ThemeDialog::ImportScheme {
+                path,
+                new_name,
+                root,
+                remap: None,
+                ..
+            } => app.import_base16_theme(path, new_name, root),
+            ThemeDialog::ImportScheme {
+                path,
+                remap: Some(mode),
+                ..
+            } => app.remap_to_base16(path, *mode),
+            ThemeDialog::ImportWal { path, mode, .. } => app.import_wal_colors(path, *mode),
+            ThemeDialog::EditTags { theme, tags } => {
+                app.set_theme_tags(theme, tags);
+                Ok(())
+            }
+        };
+        if let Err(e) = result {
+            app.error_message = Some(e);
+        }
+    } else if keep_open {
+        app.theme_dialog = Some(dialog);
+    }
+}
+
+/// Progress of a running clone or pull, with a Cancel button.
+pub fn ui_git_job_window(ctx: &egui::Context, app: &mut OmarchyApp) {
+    let Some(job) = &app.git_job else {
+        return;
+    };
+
+    let title = match &job.kind {
+        GitJobKind::Clone { theme, .. } => format!("Installing {}", theme),
+        GitJobKind::Pull { theme } => format!("Updating {}", theme),
+    };
+    egui::Window::new(title)
+        .collapsible(false)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            if let GitJobKind::Clone { url, .. } = &job.kind {
+                ui.label(egui::RichText::new(url).monospace());
+            }
+            let progress = job.transfer.progress();
+            if progress.total_objects == 0 {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Connecting…");
+                });
+            } else {
+                ui.add(
+                    egui::ProgressBar::new(
+                        progress.received_objects as f32 / progress.total_objects as f32,
+                    )
+                    .text(format!(
+                        "{} / {} objects, {} KiB",
+                        progress.received_objects,
+                        progress.total_objects,
+                        progress.received_bytes / 1024
+                    )),
+                );
+            }
+            ui.separator();
+            if job.transfer.is_cancelled() {
+                ui.label("Cancelling…");
+            } else if ui.button("Cancel").clicked() {
+                job.transfer.cancel();
+            }
+        });
+}
+
+pub fn ui_apply_output(ctx: &egui::Context, app: &mut OmarchyApp) {
+    if let Some(job) = &app.apply_job {
+        egui::Window::new("Apply theme")
+            .collapsible(false)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(&job.command).monospace());
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Running…");
+                });
+            });
+        return;
+    }
+    let Some(output) = app.apply_output.clone() else {
+        return;
+    };
+
+    let mut open = true;
+    egui::Window::new("Apply theme")
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(500.0)
+        .show(ctx, |ui| {
+            ui.label(egui::RichText::new(&output.command).monospace());
+            ui.separator();
+
+            if output.dry_run {
+                ui.colored_label(egui::Color32::YELLOW, "Dry run: command was not executed");
+                return;
+            }
+
+            match output.status {
+                None if output.timed_out => ui.colored_label(
+                    egui::Color32::RED,
+                    format!(
+                        "Stopped after {} s without finishing",
+                        APPLY_TIMEOUT.as_secs()
+                    ),
+                ),
+                Some(0) => ui.colored_label(egui::Color32::GREEN, "Exit status: 0"),
+                Some(code) => {
+                    ui.colored_label(egui::Color32::RED, format!("Exit status: {}", code))
+                }
+                None => ui.colored_label(egui::Color32::RED, "Terminated by signal"),
+            };
+
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    if !output.stdout.is_empty() {
+                        ui.label("stdout:");
+                        ui.label(egui::RichText::new(&output.stdout).monospace());
+                    }
+                    if !output.stderr.is_empty() {
+                        ui.label("stderr:");
+                        ui.label(
+                            egui::RichText::new(&output.stderr)
+                                .monospace()
+                                .color(egui::Color32::LIGHT_RED),
+                        );
+                    }
+                });
+        });
+
+    if !open {
+        app.apply_output = None;
+    }
+}
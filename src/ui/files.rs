@@ -1,5 +1,6 @@
 use crate::app::{OmarchyApp, SortMode};
 use crate::theme::scanner::get_extension;
+use crate::theme::search::name_matches;
 use eframe::egui;
 
 pub fn get_ext_color(
@@ -12,6 +13,100 @@ pub fn get_ext_color(
     crate::utils::color::get_default_ext_color(ext)
 }
 
+/// Files of a theme grouped by folder, keeping the order of `theme_files`.
+#[derive(Default)]
+struct FileTree {
+    dirs: Vec<(String, FileTree)>,
+    files: Vec<(usize, String)>,
+}
+
+impl FileTree {
+    /// Builds the tree of the files whose path matches `filter`.
+    fn build(files: &[String], filter: &str) -> Self {
+        let mut tree = FileTree::default();
+        for (i, path) in files.iter().enumerate() {
+            if !name_matches(filter, path) {
+                continue;
+            }
+            let mut node = &mut tree;
+            let mut parts: Vec<&str> = path.split('/').collect();
+            let file_name = parts.pop().unwrap_or_default();
+            for dir in parts {
+                let pos = match node.dirs.iter().position(|(name, _)| name == dir) {
+                    Some(pos) => pos,
+                    None => {
+                        node.dirs.push((dir.to_string(), FileTree::default()));
+                        node.dirs.len() - 1
+                    }
+                };
+                node = &mut node.dirs[pos].1;
+            }
+            node.files.push((i, file_name.to_string()));
+        }
+        tree
+    }
+}
+
+fn show_file_tree(
+    ui: &mut egui::Ui,
+    app: &OmarchyApp,
+    tree: &FileTree,
+    dir_path: &str,
+    selected_idx: &mut Option<usize>,
+) {
+    let use_colors = app.file_sort_mode == SortMode::Color;
+    let filtering = !app.file_filter.trim().is_empty();
+
+    for (dir_name, subtree) in &tree.dirs {
+        let path = if dir_path.is_empty() {
+            dir_name.clone()
+        } else {
+            format!("{}/{}", dir_path, dir_name)
+        };
+        let contains_selected = app
+            .selected_file_index
+            .and_then(|i| app.theme_files.get(i))
+            .is_some_and(|f| f.starts_with(&format!("{}/", path)));
+        egui::CollapsingHeader::new(format!("{}/", dir_name))
+            .id_salt(("file_dir", &path))
+            .default_open(contains_selected)
+            .open(filtering.then_some(true))
+            .show(ui, |ui| {
+                show_file_tree(ui, app, subtree, &path, selected_idx);
+            });
+    }
+
+    for (i, name) in &tree.files {
+        let is_selected = app.selected_file_index == Some(*i);
+        let editable = app
+            .theme_files
+            .get(*i)
+            .is_some_and(|f| !app.non_editable_files.contains(f));
+
+        if !editable {
+            ui.add_enabled(
+                false,
+                egui::SelectableLabel::new(false, format!("{} (binary)", name)),
+            )
+            .on_disabled_hover_text("Not a text file, copied as-is on save");
+            continue;
+        }
+
+        let text = if use_colors {
+            let ext = get_extension(name);
+            let ext_with_dot = format!(".{}", ext);
+            let color = get_ext_color(&ext_with_dot, &app.enabled_extensions);
+            eframe::egui::RichText::new(name).color(color)
+        } else {
+            eframe::egui::RichText::new(name)
+        };
+
+        if ui.selectable_label(is_selected, text).clicked() {
+            *selected_idx = Some(*i);
+        }
+    }
+}
+
 pub fn ui_files_panel(ctx: &egui::Context, app: &mut OmarchyApp) {
     egui::SidePanel::left("files_panel")
         .min_width(150.0)
@@ -38,41 +133,24 @@ pub fn ui_files_panel(ctx: &egui::Context, app: &mut OmarchyApp) {
             });
             ui.separator();
 
+            ui.add(
+                egui::TextEdit::singleline(&mut app.file_filter)
+                    .hint_text("Filter by name or .ext")
+                    .desired_width(f32::INFINITY),
+            );
+
             if app.theme_files.is_empty() {
                 ui.label("No files found");
             } else {
-                let use_colors = app.file_sort_mode == SortMode::Color;
-                let files: Vec<(usize, String)> = app
-                    .theme_files
-                    .iter()
-                    .enumerate()
-                    .map(|(i, n)| (i, n.clone()))
-                    .collect();
-
+                let tree = FileTree::build(&app.theme_files, &app.file_filter);
+                if tree.dirs.is_empty() && tree.files.is_empty() {
+                    ui.weak("No matching files");
+                }
                 let mut selected_idx: Option<usize> = None;
 
-                for (i, name) in files {
-                    let is_selected = app.selected_file_index == Some(i);
-
-                    if use_colors {
-                        let ext = get_extension(&name);
-                        let ext_with_dot = format!(".{}", ext);
-                        let color = get_ext_color(&ext_with_dot, &app.enabled_extensions);
-                        if ui
-                            .selectable_label(
-                                is_selected,
-                                eframe::egui::RichText::new(&name).color(color),
-                            )
-                            .clicked()
-                        {
-                            selected_idx = Some(i);
-                        }
-                    } else {
-                        if ui.selectable_label(is_selected, &name).clicked() {
-                            selected_idx = Some(i);
-                        }
-                    }
-                }
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    show_file_tree(ui, app, &tree, "", &mut selected_idx);
+                });
 
                 if let Some(idx) = selected_idx {
                     app.selected_file_index = Some(idx);
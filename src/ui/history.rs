@@ -0,0 +1,142 @@
+use crate::app::OmarchyApp;
+use eframe::egui;
+
+/// Formats seconds since the epoch as `YYYY-MM-DD HH:MM` (UTC).
+fn format_time(secs: i64) -> String {
+    let days = secs.div_euclid(86400);
+    let rem = secs.rem_euclid(86400);
+    // Civil from days, Howard Hinnant's algorithm
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60
+    )
+}
+
+fn diff_line(ui: &mut egui::Ui, line: &str) {
+    let color = if line.starts_with("+++") || line.starts_with("---") {
+        egui::Color32::GRAY
+    } else if line.starts_with('+') {
+        egui::Color32::LIGHT_GREEN
+    } else if line.starts_with('-') {
+        egui::Color32::LIGHT_RED
+    } else if line.starts_with("@@") || line.starts_with("diff ") {
+        egui::Color32::LIGHT_BLUE
+    } else {
+        egui::Color32::GRAY
+    };
+    ui.label(egui::RichText::new(line).monospace().color(color));
+}
+
+pub fn ui_history_window(ctx: &egui::Context, app: &mut OmarchyApp) {
+    let Some(history) = &app.history else {
+        return;
+    };
+    if app.selected_theme_name().as_deref() != Some(history.theme.as_str()) {
+        app.refresh_history();
+    }
+    let Some(history) = app.history.clone() else {
+        return;
+    };
+
+    let mut open = true;
+    let mut select: Option<usize> = None;
+    let mut revert: Option<String> = None;
+    egui::Window::new(format!("History of {}", history.theme))
+        .open(&mut open)
+        .default_size([700.0, 420.0])
+        .show(ctx, |ui| {
+            if let Some(origin) = &history.origin {
+                ui.label(format!(
+                    "Installed from {}. These are upstream versions; saves are not recorded here.",
+                    origin
+                ));
+            }
+            if history.commits.is_empty() {
+                ui.label("No versions recorded yet.");
+                if !app.history_enabled {
+                    ui.label("Enable version history in Settings to commit on Save and Overwrite.");
+                }
+                return;
+            }
+
+            ui.horizontal_top(|ui| {
+                ui.vertical(|ui| {
+                    ui.set_width(260.0);
+                    egui::ScrollArea::vertical()
+                        .id_salt("history_commits")
+                        .show(ui, |ui| {
+                            for (i, commit) in history.commits.iter().enumerate() {
+                                let text = format!(
+                                    "{}  {}\n{}",
+                                    commit.short_id(),
+                                    format_time(commit.time),
+                                    commit.summary
+                                );
+                                if ui
+                                    .selectable_label(history.selected == Some(i), text)
+                                    .clicked()
+                                {
+                                    select = Some(i);
+                                }
+                            }
+                        });
+                });
+                ui.separator();
+                ui.vertical(|ui| {
+                    let Some(commit) = history.selected.and_then(|i| history.commits.get(i)) else {
+                        ui.label("Select a version to see its changes");
+                        return;
+                    };
+                    let is_latest = history.selected == Some(0);
+                    let revert_btn = ui
+                        .add_enabled(
+                            !is_latest && !app.has_unsaved_changes && history.origin.is_none(),
+                            egui::Button::new("Revert to this version"),
+                        )
+                        .on_disabled_hover_text(if history.origin.is_some() {
+                            "Use Pull updates for themes installed from git"
+                        } else if is_latest {
+                            "This is the current version"
+                        } else {
+                            "Save or undo your changes first"
+                        });
+                    if revert_btn.clicked() {
+                        revert = Some(commit.id.clone());
+                    }
+                    ui.separator();
+                    egui::ScrollArea::both()
+                        .id_salt("history_diff")
+                        .show(ui, |ui| {
+                            for line in history.diff.lines() {
+                                diff_line(ui, line);
+                            }
+                        });
+                });
+            });
+        });
+
+    if let Some(i) = select {
+        app.select_history_commit(i);
+    }
+    if let Some(id) = revert {
+        if let Err(e) = app.revert_to_commit(&id) {
+            app.error_message = Some(e);
+        }
+    }
+    if !open {
+        app.history = None;
+    }
+}
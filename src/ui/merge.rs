@@ -0,0 +1,107 @@
+use crate::app::OmarchyApp;
+use crate::theme::merge::MergeFile;
+use crate::ui::preview::swatch;
+use eframe::egui;
+
+fn merge_file_section(ui: &mut egui::Ui, file: &mut MergeFile) {
+    let selected = file.keys.iter().filter(|k| k.selected).count();
+    let header = if file.whole_file {
+        format!("{} (whole file)", file.file)
+    } else if !file.in_target {
+        format!("{} (new file)", file.file)
+    } else {
+        format!("{} ({}/{} colors)", file.file, selected, file.keys.len())
+    };
+
+    egui::CollapsingHeader::new(header)
+        .id_salt(("merge_file", &file.file))
+        .show(ui, |ui| {
+            let label = if file.in_target {
+                "Replace the whole file"
+            } else {
+                "Add this file"
+            };
+            ui.checkbox(&mut file.whole_file, label);
+
+            ui.add_enabled_ui(!file.whole_file, |ui| {
+                for key in &mut file.keys {
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut key.selected, "");
+                        swatch(ui, key.current);
+                        ui.label("→");
+                        swatch(ui, key.incoming);
+                        ui.monospace(&key.key);
+                    });
+                }
+            });
+            if file.in_target && file.keys.is_empty() {
+                ui.weak("Same colors, other text differs");
+            }
+        });
+}
+
+pub fn ui_merge_window(ctx: &egui::Context, app: &mut OmarchyApp) {
+    let Some(target) = app.selected_theme_name() else {
+        app.merge = None;
+        return;
+    };
+    let Some(plan) = &mut app.merge else {
+        return;
+    };
+
+    let mut open = true;
+    let mut apply = false;
+    egui::Window::new(format!("Merge {} into {}", plan.source, target))
+        .open(&mut open)
+        .default_size([480.0, 420.0])
+        .show(ctx, |ui| {
+            if plan.files.is_empty() {
+                ui.label("Nothing to merge: all shared files are identical.");
+                return;
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Select all colors").clicked() {
+                    plan.files
+                        .iter_mut()
+                        .flat_map(|f| f.keys.iter_mut())
+                        .for_each(|k| k.selected = true);
+                }
+                if ui.button("Clear").clicked() {
+                    for file in &mut plan.files {
+                        file.whole_file = false;
+                        file.keys.iter_mut().for_each(|k| k.selected = false);
+                    }
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .max_height(320.0)
+                .show(ui, |ui| {
+                    for file in &mut plan.files {
+                        merge_file_section(ui, file);
+                    }
+                });
+
+            ui.separator();
+            ui.label(
+                egui::RichText::new(
+                    "The preview panel shows the result until you close this window",
+                )
+                .small()
+                .weak(),
+            );
+            if ui
+                .add_enabled(plan.has_selection(), egui::Button::new("Apply"))
+                .clicked()
+            {
+                apply = true;
+            }
+        });
+
+    if apply {
+        app.apply_merge();
+    } else if !open {
+        app.merge = None;
+    }
+}
@@ -1,9 +1,25 @@
+pub mod adjust;
 pub mod colors;
+pub mod compare;
+pub mod dialogs;
 pub mod files;
+pub mod history;
+pub mod merge;
+pub mod preview;
+pub mod search;
 pub mod settings;
+pub mod similar;
 pub mod themes;
 
+pub use adjust::ui_adjust_window;
 pub use colors::ui_colors_panel;
+pub use compare::ui_compare_window;
+pub use dialogs::{ui_apply_output, ui_git_job_window, ui_theme_dialog};
 pub use files::ui_files_panel;
+pub use history::ui_history_window;
+pub use merge::ui_merge_window;
+pub use preview::ui_preview_panel;
+pub use search::ui_color_search_window;
 pub use settings::ui_settings_panel;
+pub use similar::ui_similar_window;
 pub use themes::ui_themes_panel;
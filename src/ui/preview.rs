@@ -0,0 +1,508 @@
+use crate::app::OmarchyApp;
+use crate::theme::contrast::{check_theme_contrast, ContrastLevel, ContrastResult};
+use crate::theme::semantic::{ColorRole, PaletteEntry, ThemeColors};
+use crate::utils::cvd::{confusable_pairs, simulate, CvdMode};
+use eframe::egui::{self, Color32, FontId, Pos2, Rect, Sense, Stroke};
+
+const TERMINAL_FILES: &[&str] = &["alacritty", "kitty", "ghostty"];
+
+/// Fallback xterm palette for themes that do not define ANSI colors.
+const DEFAULT_ANSI: [Color32; 16] = [
+    Color32::from_rgb(0, 0, 0),
+    Color32::from_rgb(205, 0, 0),
+    Color32::from_rgb(0, 205, 0),
+    Color32::from_rgb(205, 205, 0),
+    Color32::from_rgb(0, 0, 238),
+    Color32::from_rgb(205, 0, 205),
+    Color32::from_rgb(0, 205, 205),
+    Color32::from_rgb(229, 229, 229),
+    Color32::from_rgb(127, 127, 127),
+    Color32::from_rgb(255, 0, 0),
+    Color32::from_rgb(0, 255, 0),
+    Color32::from_rgb(255, 255, 0),
+    Color32::from_rgb(92, 92, 255),
+    Color32::from_rgb(255, 0, 255),
+    Color32::from_rgb(0, 255, 255),
+    Color32::from_rgb(255, 255, 255),
+];
+
+/// Colors used by the mock components, resolved from the theme with
+/// fallbacks for anything the theme does not define.
+pub struct PreviewColors {
+    pub background: Color32,
+    pub foreground: Color32,
+    pub cursor: Color32,
+    pub ansi: [Color32; 16],
+    pub accent: Color32,
+    pub active_border: Color32,
+    pub inactive_border: Color32,
+    pub bar_bg: Color32,
+    pub bar_fg: Color32,
+    pub notify_bg: Color32,
+    pub notify_fg: Color32,
+    pub notify_border: Color32,
+    pub btop_bg: Color32,
+    pub btop_fg: Color32,
+    pub btop_box: Color32,
+    pub btop_title: Color32,
+    pub graph: [Color32; 3],
+}
+
+impl PreviewColors {
+    pub fn resolve(colors: &ThemeColors) -> Self {
+        let background = colors
+            .role(ColorRole::Background, TERMINAL_FILES)
+            .unwrap_or(Color32::from_gray(30));
+        let foreground = colors
+            .role(ColorRole::Foreground, TERMINAL_FILES)
+            .unwrap_or(Color32::from_gray(220));
+
+        let mut ansi = DEFAULT_ANSI;
+        for (i, slot) in ansi.iter_mut().enumerate() {
+            if let Some(c) = colors.role(ColorRole::Ansi(i as u8), TERMINAL_FILES) {
+                *slot = c;
+            }
+        }
+
+        let accent = colors
+            .role(ColorRole::Accent, &["waybar", "walker"])
+            .unwrap_or(ansi[4]);
+        let cursor = colors
+            .role(ColorRole::Cursor, TERMINAL_FILES)
+            .unwrap_or(foreground);
+        let active_border = colors
+            .role(ColorRole::ActiveBorder, &["hyprland"])
+            .unwrap_or(accent);
+        let inactive_border = colors
+            .role(ColorRole::InactiveBorder, &["hyprland"])
+            .unwrap_or(ansi[8]);
+
+        let bar_bg = colors
+            .find("waybar", &["background", "bg"])
+            .unwrap_or(background);
+        let bar_fg = colors
+            .find("waybar", &["foreground", "fg"])
+            .unwrap_or(foreground);
+
+        let notify_bg = colors
+            .find("mako", &["background-color"])
+            .unwrap_or(background);
+        let notify_fg = colors.find("mako", &["text-color"]).unwrap_or(foreground);
+        let notify_border = colors.find("mako", &["border-color"]).unwrap_or(accent);
+
+        let btop_bg = colors.find("btop", &["main_bg"]).unwrap_or(background);
+        let btop_fg = colors.find("btop", &["main_fg"]).unwrap_or(foreground);
+        let btop_box = colors
+            .find("btop", &["cpu_box", "div_line"])
+            .unwrap_or(inactive_border);
+        let btop_title = colors.find("btop", &["title"]).unwrap_or(foreground);
+        let graph = [
+            colors.find("btop", &["cpu_start"]).unwrap_or(ansi[2]),
+            colors.find("btop", &["cpu_mid"]).unwrap_or(ansi[3]),
+            colors.find("btop", &["cpu_end"]).unwrap_or(ansi[1]),
+        ];
+
+        Self {
+            background,
+            foreground,
+            cursor,
+            ansi,
+            accent,
+            active_border,
+            inactive_border,
+            bar_bg,
+            bar_fg,
+            notify_bg,
+            notify_fg,
+            notify_border,
+            btop_bg,
+            btop_fg,
+            btop_box,
+            btop_title,
+            graph,
+        }
+    }
+}
+
+/// Theme colors of the loaded theme including the color currently being
+/// picked, a pending merge and a pending adjustment, so the preview follows
+/// them live.
+pub fn live_theme_colors(app: &OmarchyApp) -> ThemeColors {
+    let mut colors = match &app.merge {
+        Some(plan) => ThemeColors::from_cache(&plan.apply(&app.file_cache)),
+        None => ThemeColors::from_cache(&app.file_cache),
+    };
+    if let (Some(target), Some(picked)) = (&app.color_edit_target, app.picker_color) {
+        for c in colors
+            .colors
+            .iter_mut()
+            .filter(|c| c.file == target.file_name && c.color.id == target.color_id)
+        {
+            c.color.value = picked;
+        }
+    }
+    if app.show_adjust {
+        for c in &mut colors.colors {
+            c.color.value = app.adjustment.apply(c.color.value);
+        }
+    }
+    colors
+}
+
+/// Draws colored text segments on one line, returning the x after the last one.
+fn text_run(painter: &egui::Painter, pos: Pos2, segments: &[(&str, Color32)]) -> f32 {
+    let mut x = pos.x;
+    for (text, color) in segments {
+        let galley = painter.layout_no_wrap(text.to_string(), FontId::monospace(11.0), *color);
+        let width = galley.size().x;
+        painter.galley(Pos2::new(x, pos.y), galley, *color);
+        x += width;
+    }
+    x
+}
+
+fn lerp_color(a: Color32, b: Color32, t: f32) -> Color32 {
+    let mix = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * t).round() as u8;
+    Color32::from_rgb(mix(a.r(), b.r()), mix(a.g(), b.g()), mix(a.b(), b.b()))
+}
+
+fn draw_terminal(ui: &mut egui::Ui, c: &PreviewColors) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), 150.0), Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 4.0, c.background);
+    painter.rect_stroke(
+        rect,
+        4.0,
+        Stroke::new(2.0, c.active_border),
+        egui::StrokeKind::Inside,
+    );
+
+    let x = rect.left() + 10.0;
+    let mut y = rect.top() + 8.0;
+    let line = 15.0;
+
+    text_run(
+        &painter,
+        Pos2::new(x, y),
+        &[
+            ("user@omarchy", c.ansi[2]),
+            (":", c.foreground),
+            ("~/themes", c.ansi[4]),
+            ("$ ", c.foreground),
+            ("ls", c.foreground),
+        ],
+    );
+    y += line;
+    text_run(
+        &painter,
+        Pos2::new(x, y),
+        &[
+            ("backgrounds/  ", c.ansi[4]),
+            ("alacritty.toml  ", c.foreground),
+            ("apply.sh", c.ansi[2]),
+        ],
+    );
+    y += line;
+    text_run(
+        &painter,
+        Pos2::new(x, y),
+        &[
+            ("error: ", c.ansi[1]),
+            ("warning: ", c.ansi[3]),
+            ("info ", c.ansi[6]),
+            ("done", c.ansi[5]),
+        ],
+    );
+    y += line;
+    let prompt_end = text_run(&painter, Pos2::new(x, y), &[("$ ", c.foreground)]);
+    painter.rect_filled(
+        Rect::from_min_size(Pos2::new(prompt_end, y), egui::vec2(7.0, 13.0)),
+        0.0,
+        c.cursor,
+    );
+
+    // ANSI palette, normal colors on top and bright below
+    y += line + 6.0;
+    let swatch = ((rect.width() - 20.0) / 8.0).min(30.0);
+    for (i, color) in c.ansi.iter().enumerate() {
+        let col = (i % 8) as f32;
+        let row = (i / 8) as f32;
+        let min = Pos2::new(x + col * swatch, y + row * 18.0);
+        painter.rect_filled(
+            Rect::from_min_size(min, egui::vec2(swatch - 2.0, 16.0)),
+            2.0,
+            *color,
+        );
+    }
+}
+
+fn draw_waybar(ui: &mut egui::Ui, c: &PreviewColors) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), 24.0), Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, c.bar_bg);
+
+    let y = rect.center().y - 7.0;
+    let mut x = rect.left() + 8.0;
+    for ws in 1..=5 {
+        let color = if ws == 2 { c.accent } else { c.bar_fg };
+        x = text_run(&painter, Pos2::new(x, y), &[(&format!("{} ", ws), color)]) + 4.0;
+    }
+    painter.text(
+        Pos2::new(rect.center().x, rect.center().y),
+        egui::Align2::CENTER_CENTER,
+        "Sat 12:34",
+        FontId::monospace(11.0),
+        c.bar_fg,
+    );
+    painter.text(
+        Pos2::new(rect.right() - 8.0, rect.center().y),
+        egui::Align2::RIGHT_CENTER,
+        "vol 60%  bat 87%",
+        FontId::monospace(11.0),
+        c.bar_fg,
+    );
+}
+
+fn draw_windows(ui: &mut egui::Ui, c: &PreviewColors) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), 90.0), Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, lerp_color(c.background, Color32::BLACK, 0.4));
+
+    let gap = 8.0;
+    let width = (rect.width() - gap * 3.0) / 2.0;
+    let inactive = Rect::from_min_size(
+        rect.min + egui::vec2(gap, gap),
+        egui::vec2(width, rect.height() - gap * 2.0),
+    );
+    let active = inactive.translate(egui::vec2(width + gap, 0.0));
+
+    for (win, border, label) in [
+        (inactive, c.inactive_border, "inactive"),
+        (active, c.active_border, "active"),
+    ] {
+        painter.rect_filled(win, 3.0, c.background);
+        painter.rect_stroke(win, 3.0, Stroke::new(2.0, border), egui::StrokeKind::Inside);
+        painter.text(
+            win.center(),
+            egui::Align2::CENTER_CENTER,
+            label,
+            FontId::proportional(12.0),
+            c.foreground,
+        );
+    }
+}
+
+fn draw_notification(ui: &mut egui::Ui, c: &PreviewColors) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), 52.0), Sense::hover());
+    let painter = ui.painter_at(rect);
+    let card = rect.shrink2(egui::vec2(rect.width() * 0.15, 2.0));
+    painter.rect_filled(card, 6.0, c.notify_bg);
+    painter.rect_stroke(
+        card,
+        6.0,
+        Stroke::new(2.0, c.notify_border),
+        egui::StrokeKind::Inside,
+    );
+    painter.text(
+        card.left_top() + egui::vec2(10.0, 8.0),
+        egui::Align2::LEFT_TOP,
+        "Omarchy",
+        FontId::proportional(13.0),
+        c.notify_fg,
+    );
+    painter.text(
+        card.left_top() + egui::vec2(10.0, 26.0),
+        egui::Align2::LEFT_TOP,
+        "Theme preview updated",
+        FontId::proportional(12.0),
+        c.notify_fg,
+    );
+}
+
+fn draw_btop(ui: &mut egui::Ui, c: &PreviewColors) {
+    const LOAD: [f32; 24] = [
+        0.2, 0.3, 0.25, 0.4, 0.55, 0.5, 0.7, 0.9, 0.85, 0.6, 0.45, 0.5, 0.65, 0.8, 1.0, 0.75, 0.5,
+        0.35, 0.3, 0.45, 0.6, 0.4, 0.3, 0.2,
+    ];
+
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), 90.0), Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, c.btop_bg);
+    let frame = rect.shrink(4.0);
+    painter.rect_stroke(
+        frame,
+        4.0,
+        Stroke::new(1.0, c.btop_box),
+        egui::StrokeKind::Inside,
+    );
+    painter.text(
+        frame.left_top() + egui::vec2(10.0, -1.0),
+        egui::Align2::LEFT_TOP,
+        "cpu",
+        FontId::monospace(11.0),
+        c.btop_title,
+    );
+    painter.text(
+        frame.right_top() + egui::vec2(-10.0, -1.0),
+        egui::Align2::RIGHT_TOP,
+        "42%",
+        FontId::monospace(11.0),
+        c.btop_fg,
+    );
+
+    let graph = frame.shrink2(egui::vec2(10.0, 16.0));
+    let bar_width = graph.width() / LOAD.len() as f32;
+    for (i, load) in LOAD.iter().enumerate() {
+        let color = if *load < 0.5 {
+            lerp_color(c.graph[0], c.graph[1], load * 2.0)
+        } else {
+            lerp_color(c.graph[1], c.graph[2], (load - 0.5) * 2.0)
+        };
+        let height = graph.height() * load;
+        let bar = Rect::from_min_max(
+            Pos2::new(graph.left() + i as f32 * bar_width, graph.bottom() - height),
+            Pos2::new(
+                graph.left() + (i as f32 + 1.0) * bar_width - 1.0,
+                graph.bottom(),
+            ),
+        );
+        painter.rect_filled(bar, 0.0, color);
+    }
+}
+
+fn contrast_badge(ui: &mut egui::Ui, level: ContrastLevel) {
+    let color = match level {
+        ContrastLevel::Aaa | ContrastLevel::Aa => Color32::from_rgb(46, 160, 67),
+        ContrastLevel::AaLarge => Color32::from_rgb(210, 153, 34),
+        ContrastLevel::Fail => Color32::from_rgb(218, 54, 51),
+    };
+    let text = egui::RichText::new(format!(" {} ", level.label()))
+        .small()
+        .strong()
+        .color(Color32::WHITE)
+        .background_color(color);
+    ui.label(text);
+}
+
+fn draw_contrast_results(ui: &mut egui::Ui, results: &[ContrastResult], show_apca: bool) {
+    if results.is_empty() {
+        ui.label("No known foreground/background pairs found");
+        return;
+    }
+
+    egui::Grid::new("contrast_grid")
+        .num_columns(if show_apca { 5 } else { 4 })
+        .spacing([8.0, 4.0])
+        .show(ui, |ui| {
+            for result in results {
+                let (rect, _) = ui.allocate_exact_size(egui::vec2(28.0, 18.0), Sense::hover());
+                ui.painter().rect_filled(rect, 2.0, result.background);
+                ui.painter().text(
+                    rect.center(),
+                    egui::Align2::CENTER_CENTER,
+                    "Aa",
+                    FontId::proportional(12.0),
+                    result.foreground,
+                );
+
+                let label = ui.label(&result.label);
+                label.on_hover_text(&result.file);
+                ui.label(format!("{:.2}:1", result.ratio));
+                contrast_badge(ui, result.level());
+                if show_apca {
+                    ui.label(format!("Lc {:.0}", result.apca));
+                }
+                ui.end_row();
+            }
+        });
+}
+
+/// A small filled square showing `color`.
+pub fn swatch(ui: &mut egui::Ui, color: Color32) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(18.0, 18.0), Sense::hover());
+    ui.painter().rect_filled(rect, 2.0, color);
+    response
+}
+
+/// Lists palette entries that become indistinguishable under `mode`, showing
+/// both the real and the simulated colors.
+fn draw_confusable_colors(ui: &mut egui::Ui, palette: &[PaletteEntry], mode: CvdMode) {
+    let colors: Vec<Color32> = palette.iter().map(|e| e.color).collect();
+    let pairs = confusable_pairs(&colors, mode);
+    if pairs.is_empty() {
+        ui.colored_label(Color32::GREEN, "All distinct colors stay distinguishable");
+        return;
+    }
+
+    for (i, j, delta) in pairs {
+        let (a, b) = (&palette[i], &palette[j]);
+        ui.horizontal(|ui| {
+            swatch(ui, a.color).on_hover_text(a.keys.join("\n"));
+            swatch(ui, b.color).on_hover_text(b.keys.join("\n"));
+            ui.label("→");
+            swatch(ui, simulate(a.color, mode));
+            swatch(ui, simulate(b.color, mode));
+            ui.label(format!("ΔE {:.1}", delta));
+            ui.label(
+                egui::RichText::new(format!(
+                    "{} / {}",
+                    a.keys.first().map(String::as_str).unwrap_or(""),
+                    b.keys.first().map(String::as_str).unwrap_or("")
+                ))
+                .small()
+                .color(Color32::GRAY),
+            );
+        });
+    }
+}
+
+pub fn ui_preview_panel(ctx: &egui::Context, app: &mut OmarchyApp) {
+    egui::SidePanel::right("preview_panel")
+        .default_width(320.0)
+        .min_width(240.0)
+        .show(ctx, |ui| {
+            ui.heading("Preview");
+            ui.separator();
+
+            let theme_colors = live_theme_colors(app);
+            let colors = PreviewColors::resolve(&theme_colors.simulated(app.cvd_mode));
+            let contrast = check_theme_contrast(&theme_colors);
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.label("Terminal");
+                draw_terminal(ui, &colors);
+                ui.add_space(8.0);
+
+                ui.label("Waybar");
+                draw_waybar(ui, &colors);
+                ui.add_space(8.0);
+
+                ui.label("Hyprland borders");
+                draw_windows(ui, &colors);
+                ui.add_space(8.0);
+
+                ui.label("Notification");
+                draw_notification(ui, &colors);
+                ui.add_space(8.0);
+
+                ui.label("btop");
+                draw_btop(ui, &colors);
+                ui.add_space(8.0);
+
+                if app.cvd_mode != CvdMode::Normal {
+                    egui::CollapsingHeader::new(format!("{} conflicts", app.cvd_mode.label()))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            draw_confusable_colors(ui, &theme_colors.palette(), app.cvd_mode);
+                        });
+                }
+
+                egui::CollapsingHeader::new("Contrast (WCAG 2)")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        ui.checkbox(&mut app.show_apca, "Show APCA Lc");
+                        draw_contrast_results(ui, &contrast, app.show_apca);
+                    });
+            });
+        });
+}
@@ -0,0 +1,78 @@
+use crate::app::OmarchyApp;
+use crate::config::color_to_hex;
+use crate::ui::preview::swatch;
+use eframe::egui;
+
+pub fn ui_color_search_window(ctx: &egui::Context, app: &mut OmarchyApp) {
+    let Some(search) = &mut app.color_search else {
+        return;
+    };
+
+    let mut open = true;
+    let mut run = false;
+    let mut open_file: Option<(String, String)> = None;
+    egui::Window::new("Find color in themes")
+        .open(&mut open)
+        .default_size([520.0, 440.0])
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut search.query)
+                        .hint_text("#89b4fa, #89b4fa~5 (ΔE) or a key"),
+                );
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    run = true;
+                }
+                if ui.button("Search").clicked() {
+                    run = true;
+                }
+            });
+            ui.separator();
+
+            let Some(results) = &search.results else {
+                ui.label("Searches every theme, including unsaved edits of the loaded one");
+                return;
+            };
+            if results.is_empty() {
+                ui.label("No theme uses this color");
+                return;
+            }
+            ui.label(format!("Used by {} themes", results.len()));
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for result in results {
+                    let header = format!("{} ({})", result.theme, result.matches.len());
+                    egui::CollapsingHeader::new(header)
+                        .id_salt(("color_search", &result.theme))
+                        .show(ui, |ui| {
+                            for m in &result.matches {
+                                ui.horizontal(|ui| {
+                                    swatch(ui, m.color);
+                                    ui.monospace(color_to_hex(m.color));
+                                    if m.delta_e > 0.0 {
+                                        ui.weak(format!("ΔE {:.1}", m.delta_e));
+                                    }
+                                    let label = format!("{}: {}", m.file, m.key);
+                                    if ui.link(label).clicked() {
+                                        open_file = Some((result.theme.clone(), m.file.clone()));
+                                    }
+                                });
+                            }
+                        });
+                }
+            });
+        });
+
+    if run {
+        app.search_color_in_themes();
+    }
+    if let Some((theme, file)) = open_file {
+        if let Some(search) = &app.color_search {
+            app.color_filter = search.query.clone();
+        }
+        app.open_theme_file(&theme, &file);
+    }
+    if !open {
+        app.color_search = None;
+    }
+}
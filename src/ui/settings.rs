@@ -1,6 +1,25 @@
 use crate::app::OmarchyApp;
 use eframe::egui;
 
+/// Editable list of paths or names, with a remove button per entry.
+fn string_list(ui: &mut egui::Ui, list: &mut Vec<String>, add_label: &str) {
+    let mut remove: Option<usize> = None;
+    for (i, item) in list.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(item).desired_width(400.0));
+            if ui.small_button("✕").clicked() {
+                remove = Some(i);
+            }
+        });
+    }
+    if let Some(i) = remove {
+        list.remove(i);
+    }
+    if ui.button(add_label).clicked() {
+        list.push(String::new());
+    }
+}
+
 pub fn ui_settings_panel(ctx: &egui::Context, app: &mut OmarchyApp) {
     egui::CentralPanel::default().show(ctx, |ui| {
         ui.heading("Settings");
@@ -19,11 +38,45 @@ pub fn ui_settings_panel(ctx: &egui::Context, app: &mut OmarchyApp) {
         ui.label("Omarchy Themes Directory:");
         ui.add(egui::TextEdit::singleline(&mut app.themes_path).desired_width(400.0));
 
+        ui.label("More theme folders (listed after it, e.g. system or dotfiles themes):");
+        string_list(ui, &mut app.extra_theme_paths, "Add folder");
+
+        ui.separator();
+
+        egui::CollapsingHeader::new("Protected themes (only saved as a copy)")
+            .id_salt("protection_settings")
+            .show(ui, |ui| {
+                ui.label("Themes:");
+                string_list(ui, &mut app.protected_themes, "Add theme");
+                ui.label("Folders whose themes are all protected:");
+                string_list(ui, &mut app.protected_roots, "Add folder");
+                ui.label("Package-managed folders (themes linked into these are protected):");
+                string_list(ui, &mut app.package_paths, "Add folder");
+            });
+
         ui.separator();
 
         ui.label("Save Prefix (for new themes):");
         ui.add(egui::TextEdit::singleline(&mut app.save_prefix).desired_width(200.0));
 
+        ui.separator();
+
+        ui.label("Apply Command ({name} and {path} are replaced):");
+        ui.add(egui::TextEdit::singleline(&mut app.apply_command).desired_width(400.0));
+        ui.checkbox(&mut app.apply_dry_run, "Dry run (only show the command)");
+
+        ui.separator();
+
+        ui.label("pywal / wallust colors.json:");
+        ui.add(egui::TextEdit::singleline(&mut app.wal_colors_path).desired_width(400.0));
+
+        ui.separator();
+
+        ui.checkbox(
+            &mut app.history_enabled,
+            "Version history (commit each Save and Overwrite to git in the theme folder)",
+        );
+
         ui.separator();
         ui.label("Enabled File Extensions:");
 
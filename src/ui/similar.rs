@@ -0,0 +1,59 @@
+use crate::app::OmarchyApp;
+use crate::ui::adjust::palette_strip;
+use eframe::egui::{self, Color32};
+
+pub fn ui_similar_window(ctx: &egui::Context, app: &mut OmarchyApp) {
+    let Some(similar) = &app.similar else {
+        return;
+    };
+
+    let can_compare = app.selected_theme_name().as_deref() == Some(similar.theme.as_str());
+    let mut open = true;
+    let mut select: Option<String> = None;
+    let mut compare: Option<String> = None;
+    egui::Window::new(format!("Themes similar to {}", similar.theme))
+        .open(&mut open)
+        .default_size([460.0, 420.0])
+        .show(ctx, |ui| {
+            if similar.ranked.is_empty() {
+                ui.label("No other themes with colors");
+                return;
+            }
+            ui.weak("Distance is CIEDE2000 ΔE of background, foreground and palette");
+            ui.separator();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("similar_themes")
+                    .num_columns(4)
+                    .striped(true)
+                    .spacing([12.0, 4.0])
+                    .show(ui, |ui| {
+                        for (name, signature, distance) in &similar.ranked {
+                            let colors: Vec<Color32> =
+                                signature.colors.iter().map(|(c, _)| *c).collect();
+                            ui.allocate_ui(egui::vec2(120.0, 14.0), |ui| {
+                                palette_strip(ui, &colors, 14.0);
+                            });
+                            if ui.link(name).clicked() {
+                                select = Some(name.clone());
+                            }
+                            ui.monospace(format!("{:.1}", distance));
+                            if can_compare && ui.small_button("Compare").clicked() {
+                                compare = Some(name.clone());
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+
+    if let Some(name) = compare {
+        app.compare_with_selected(&name);
+    }
+    if let Some(name) = select {
+        app.select_theme(&name);
+    }
+    if !open {
+        app.similar = None;
+    }
+}
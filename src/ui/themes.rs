@@ -1,6 +1,204 @@
-use crate::app::{OmarchyApp, SortMode};
+use crate::app::{OmarchyApp, SortMode, ThemeDialog};
+use crate::theme::export::PaletteFormat;
+use crate::theme::ops::unique_theme_name;
+use crate::theme::roots::Protection;
+use crate::theme::search::name_matches;
+use crate::theme::semantic::RemapMode;
+use crate::ui::adjust::palette_strip;
 use eframe::egui;
 
+const THUMB_WIDTH: f32 = 48.0;
+/// Time spent loading thumbnails per frame, at least one is always loaded.
+const THUMBNAIL_FRAME_BUDGET: std::time::Duration = std::time::Duration::from_millis(30);
+
+fn theme_context_menu(ui: &mut egui::Ui, app: &mut OmarchyApp, name: &str) {
+    let favorite_label = if app.favorites.contains(name) {
+        "Remove from favorites"
+    } else {
+        "Add to favorites"
+    };
+    if ui.button(favorite_label).clicked() {
+        app.toggle_favorite(name);
+        ui.close_menu();
+    }
+    if ui.button("Tags…").clicked() {
+        app.theme_dialog = Some(ThemeDialog::EditTags {
+            theme: name.to_string(),
+            tags: app
+                .theme_tags
+                .get(name)
+                .map(|tags| tags.join(", "))
+                .unwrap_or_default(),
+        });
+        ui.close_menu();
+    }
+    // Folder and package protection come from the config, only the theme list is toggled here
+    if matches!(app.protection(name), None | Some(Protection::Listed)) {
+        let protect_label = if app.is_protected_theme(name) {
+            "Unprotect"
+        } else {
+            "Protect from overwrite"
+        };
+        if ui.button(protect_label).clicked() {
+            app.toggle_protected(name);
+            ui.close_menu();
+        }
+    }
+    ui.separator();
+    // Why the theme's files can't be changed in place, if they can't
+    let locked = if app.is_read_only(name) {
+        Some("In a read-only or protected theme folder".to_string())
+    } else {
+        app.protection(name)
+            .map(|protection| format!("This theme {}", protection.describe()))
+    };
+    let rename_btn = ui
+        .add_enabled(locked.is_none(), egui::Button::new("Rename…"))
+        .on_disabled_hover_text(locked.clone().unwrap_or_default());
+    if rename_btn.clicked() {
+        app.theme_dialog = Some(ThemeDialog::Rename {
+            theme: name.to_string(),
+            new_name: name.to_string(),
+        });
+        ui.close_menu();
+    }
+    if ui.button("Duplicate…").clicked() {
+        app.theme_dialog = Some(ThemeDialog::Duplicate {
+            theme: name.to_string(),
+            new_name: unique_theme_name(&app.root_paths(), &format!("{}-copy", name)),
+        });
+        ui.close_menu();
+    }
+    if let Some(selected) = app.selected_theme_name().filter(|s| s != name) {
+        if ui
+            .button(format!("Compare with \"{}\"", selected))
+            .clicked()
+        {
+            app.compare_with_selected(name);
+            ui.close_menu();
+        }
+        if ui.button(format!("Merge into \"{}\"…", selected)).clicked() {
+            app.start_merge_from(name);
+            ui.close_menu();
+        }
+    }
+    if ui.button("Find similar themes").clicked() {
+        app.find_similar_themes(name);
+        ui.close_menu();
+    }
+    if app.theme_origin_url(name).is_some()
+        && ui
+            .add_enabled(locked.is_none(), egui::Button::new("Pull updates"))
+            .on_disabled_hover_text(locked.clone().unwrap_or_default())
+            .clicked()
+    {
+        if let Err(e) = app.pull_theme_updates(name) {
+            app.error_message = Some(e);
+        }
+        ui.close_menu();
+    }
+    if ui.button("Open folder").clicked() {
+        if let Err(e) = app.open_theme_folder(name) {
+            app.error_message = Some(e);
+        }
+        ui.close_menu();
+    }
+    if ui.button("Export as .tar.gz…").clicked() {
+        let path = dirs::home_dir()
+            .unwrap_or_default()
+            .join(format!("{}.tar.gz", name));
+        app.theme_dialog = Some(ThemeDialog::ExportArchive {
+            theme: name.to_string(),
+            path: path.to_string_lossy().into_owned(),
+        });
+        ui.close_menu();
+    }
+    if ui.button("Export palette…").clicked() {
+        let format = PaletteFormat::Gpl;
+        let path =
+            dirs::home_dir()
+                .unwrap_or_default()
+                .join(format!("{}{}", name, format.extension()));
+        app.theme_dialog = Some(ThemeDialog::ExportPalette {
+            theme: name.to_string(),
+            format,
+            path: path.to_string_lossy().into_owned(),
+        });
+        ui.close_menu();
+    }
+    ui.separator();
+    let delete_btn = ui
+        .add_enabled(locked.is_none(), egui::Button::new("Delete…"))
+        .on_disabled_hover_text(locked.unwrap_or_default());
+    if delete_btn.clicked() {
+        app.theme_dialog = Some(ThemeDialog::Delete {
+            theme: name.to_string(),
+        });
+        ui.close_menu();
+    }
+}
+
+/// One theme in the list: wallpaper thumbnail, name and palette strip.
+/// Themes without a thumbnail yet are queued in `to_load` when visible.
+fn theme_row(
+    ui: &mut egui::Ui,
+    app: &mut OmarchyApp,
+    i: usize,
+    name: &str,
+    selected_idx: &mut Option<usize>,
+    to_load: &mut Vec<String>,
+) {
+    let is_selected = app.selected_theme_index == Some(i);
+    let thumb = app.theme_thumbnails.get(name);
+    let row = ui.horizontal(|ui| {
+        let size = egui::vec2(THUMB_WIDTH, THUMB_WIDTH * 9.0 / 16.0);
+        match thumb.and_then(|t| t.wallpaper.as_ref()) {
+            Some(texture) => {
+                ui.add(egui::Image::new(texture).fit_to_exact_size(size));
+            }
+            None => {
+                ui.allocate_exact_size(size, egui::Sense::hover());
+            }
+        }
+        ui.vertical(|ui| {
+            let mut label = if app.favorites.contains(name) {
+                format!("★ {}", name)
+            } else {
+                name.to_string()
+            };
+            let protection = app.protection(name);
+            if protection.is_some() {
+                label.push_str(" 🔒");
+            }
+            let mut response = ui.selectable_label(is_selected, label);
+            if let Some(protection) = protection {
+                response = response.on_hover_text(format!(
+                    "Protected: {} {}",
+                    name,
+                    protection.describe()
+                ));
+            }
+            if let Some(tags) = app.theme_tags.get(name) {
+                response = response.on_hover_text(tags.join(", "));
+            }
+            if let Some(thumb) = thumb {
+                palette_strip(ui, &thumb.palette, 5.0);
+            }
+            response
+        })
+        .inner
+    });
+    if thumb.is_none() && ui.is_rect_visible(row.response.rect) {
+        to_load.push(name.to_string());
+    }
+
+    let response = row.inner;
+    if response.clicked() {
+        *selected_idx = Some(i);
+    }
+    response.context_menu(|ui| theme_context_menu(ui, app, name));
+}
+
 pub fn ui_themes_panel(ctx: &egui::Context, app: &mut OmarchyApp) {
     egui::SidePanel::left("themes_panel")
         .min_width(150.0)
@@ -15,10 +213,71 @@ pub fn ui_themes_panel(ctx: &egui::Context, app: &mut OmarchyApp) {
                     app.sort_themes(SortMode::Name);
                 }
 
+                let c_active = app.theme_sort_mode == SortMode::Color;
+                if ui
+                    .selectable_label(c_active, "C")
+                    .on_hover_text("Sort by color: dark first, then by accent hue")
+                    .clicked()
+                {
+                    app.sort_themes(SortMode::Color);
+                }
+
                 let l_active = app.theme_sort_mode == SortMode::LastOpened;
                 if ui.selectable_label(l_active, "L").clicked() {
                     app.sort_themes(SortMode::LastOpened);
                 }
+
+                if ui
+                    .selectable_label(app.pin_favorites, "★")
+                    .on_hover_text("Pin favorites at the top")
+                    .clicked()
+                {
+                    app.pin_favorites = !app.pin_favorites;
+                    app.sort_themes(app.theme_sort_mode);
+                    app.save_state();
+                }
+
+                ui.menu_button("Import", |ui| {
+                    if ui.button("From git…").clicked() {
+                        app.theme_dialog = Some(ThemeDialog::ImportGit {
+                            url: String::new(),
+                            new_name: String::new(),
+                            root: app.default_import_root(),
+                        });
+                        ui.close_menu();
+                    }
+                    if ui.button("Theme archive (.tar.gz)…").clicked() {
+                        app.theme_dialog = Some(ThemeDialog::ImportArchive {
+                            path: String::new(),
+                            new_name: String::new(),
+                            root: app.default_import_root(),
+                            info: None,
+                        });
+                        ui.close_menu();
+                    }
+                    if ui.button("base16/base24 scheme…").clicked() {
+                        app.theme_dialog = Some(ThemeDialog::ImportScheme {
+                            path: String::new(),
+                            new_name: String::new(),
+                            root: app.default_import_root(),
+                            remap: None,
+                            scheme: None,
+                        });
+                        ui.close_menu();
+                    }
+                    let wal_btn = ui.add_enabled(
+                        app.selected_theme_index.is_some(),
+                        egui::Button::new("pywal/wallust colors…"),
+                    );
+                    if wal_btn.clicked() {
+                        app.theme_dialog = Some(ThemeDialog::ImportWal {
+                            path: app.wal_colors_path.clone(),
+                            mode: RemapMode::SemanticKey,
+                            colors: None,
+                        });
+                        ui.close_menu();
+                    }
+                });
             });
             ui.separator();
 
@@ -26,6 +285,34 @@ pub fn ui_themes_panel(ctx: &egui::Context, app: &mut OmarchyApp) {
                 ui.colored_label(egui::Color32::YELLOW, err);
                 ui.separator();
             }
+            if let Some(ref status) = app.status_message {
+                ui.colored_label(egui::Color32::LIGHT_GREEN, status);
+                ui.separator();
+            }
+
+            ui.add(
+                egui::TextEdit::singleline(&mut app.theme_filter)
+                    .hint_text("Filter themes")
+                    .desired_width(f32::INFINITY),
+            );
+            let tags = app.all_tags();
+            if !tags.is_empty() {
+                egui::ComboBox::from_id_salt("tag_filter")
+                    .selected_text(app.tag_filter.as_deref().unwrap_or("All tags"))
+                    .width(ui.available_width())
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut app.tag_filter, None, "All tags");
+                        for tag in tags {
+                            ui.selectable_value(&mut app.tag_filter, Some(tag.clone()), tag);
+                        }
+                    });
+            }
+
+            if app.theme_roots.len() == 1 && app.theme_roots[0].read_only {
+                ui.weak("Read-only theme folder, changes can't be saved");
+            } else if app.theme_roots.len() == 1 && app.theme_roots[0].protected {
+                ui.weak("Protected theme folder, changes can't be saved");
+            }
 
             if app.theme_names.is_empty() {
                 ui.label("No themes found");
@@ -35,15 +322,63 @@ pub fn ui_themes_panel(ctx: &egui::Context, app: &mut OmarchyApp) {
                     .theme_names
                     .iter()
                     .enumerate()
+                    .filter(|(_, n)| name_matches(&app.theme_filter, n))
+                    .filter(|(_, n)| {
+                        app.tag_filter
+                            .as_ref()
+                            .is_none_or(|tag| app.has_tag(n, tag))
+                    })
                     .map(|(i, n)| (i, n.clone()))
                     .collect();
+                if theme_names.is_empty() {
+                    ui.weak("No matching themes");
+                }
 
                 let mut selected_idx: Option<usize> = None;
+                let mut to_load: Vec<String> = Vec::new();
 
-                for (i, name) in theme_names {
-                    let is_selected = app.selected_theme_index == Some(i);
-                    if ui.selectable_label(is_selected, &name).clicked() {
-                        selected_idx = Some(i);
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    if app.theme_roots.len() < 2 {
+                        for (i, name) in theme_names {
+                            theme_row(ui, app, i, &name, &mut selected_idx, &mut to_load);
+                        }
+                        return;
+                    }
+                    for (root_idx, root) in app.theme_roots.clone().into_iter().enumerate() {
+                        let rows: Vec<&(usize, String)> = theme_names
+                            .iter()
+                            .filter(|(_, n)| app.theme_root_index.get(n) == Some(&root_idx))
+                            .collect();
+                        let header = if root.read_only {
+                            format!("{} (read-only)", root.path)
+                        } else if root.protected {
+                            format!("{} (protected)", root.path)
+                        } else {
+                            root.path.clone()
+                        };
+                        egui::CollapsingHeader::new(header)
+                            .id_salt(("theme_root", root_idx))
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                if rows.is_empty() {
+                                    ui.weak("No themes");
+                                }
+                                for (i, name) in rows {
+                                    theme_row(ui, app, *i, name, &mut selected_idx, &mut to_load);
+                                }
+                            });
+                    }
+                });
+
+                // Spread over frames so a large folder doesn't freeze the first paint
+                if !to_load.is_empty() {
+                    ctx.request_repaint();
+                }
+                let start = std::time::Instant::now();
+                for name in to_load {
+                    app.load_theme_thumbnail(ctx, &name);
+                    if start.elapsed() > THUMBNAIL_FRAME_BUDGET {
+                        break;
                     }
                 }
 
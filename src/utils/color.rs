@@ -1,6 +1,17 @@
+use std::sync::LazyLock;
+
 use egui::Color32;
 use regex::Regex;
 
+static HEX_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"#([0-9a-fA-F]{3}|[0-9a-fA-F]{6}|[0-9a-fA-F]{8})\b").unwrap());
+static RGB_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"rgba?\s*\(\s*(\d+)\s*,\s*(\d+)\s*,\s*(\d+)\s*(?:,\s*([\d.]+))?\s*\)").unwrap()
+});
+/// Hyprland style `rgb(89b4fa)` / `rgba(89b4faee)`.
+static HYPR_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"rgba?\(([0-9a-fA-F]{8}|[0-9a-fA-F]{6})\)").unwrap());
+
 pub const DEFAULT_EXT_COLORS: &[Color32] = &[
     Color32::from_rgb(255, 107, 107),
     Color32::from_rgb(78, 205, 196),
@@ -30,24 +41,89 @@ pub struct DetectedColor {
     pub hex_text: String,
 }
 
+/// Black or white, whichever has the higher WCAG contrast against `color`.
 pub fn get_contrast_color(color: Color32) -> Color32 {
-    let r = color.r() as u32;
-    let g = color.g() as u32;
-    let b = color.b() as u32;
-    let brightness = (r + g + b) / 3;
-    if brightness > 128 {
+    if contrast_ratio(color, Color32::BLACK) >= contrast_ratio(color, Color32::WHITE) {
         Color32::BLACK
     } else {
         Color32::WHITE
     }
 }
 
+/// sRGB channel (0-255) to linear light.
+pub fn srgb_to_linear(channel: u8) -> f32 {
+    let c = channel as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// WCAG 2.x relative luminance.
+pub fn relative_luminance(color: Color32) -> f32 {
+    let [r, g, b, _] = color.to_srgba_unmultiplied();
+    0.2126 * srgb_to_linear(r) + 0.7152 * srgb_to_linear(g) + 0.0722 * srgb_to_linear(b)
+}
+
+/// WCAG 2.x contrast ratio, from 1.0 to 21.0. Order of the colors does not matter.
+pub fn contrast_ratio(a: Color32, b: Color32) -> f32 {
+    let la = relative_luminance(a);
+    let lb = relative_luminance(b);
+    let (lighter, darker) = if la > lb { (la, lb) } else { (lb, la) };
+    (lighter + 0.05) / (darker + 0.05)
+}
+
+/// APCA (0.0.98G-4g) lightness contrast Lc of `text` on `background`.
+/// Positive for dark text on light backgrounds, negative for light on dark.
+pub fn apca_contrast(text: Color32, background: Color32) -> f32 {
+    let screen_y = |c: Color32| {
+        let lin = |v: u8| (v as f32 / 255.0).powf(2.4);
+        let [r, g, b, _] = c.to_srgba_unmultiplied();
+        let y = 0.2126729 * lin(r) + 0.7151522 * lin(g) + 0.0721750 * lin(b);
+        if y < 0.022 {
+            y + (0.022 - y).powf(1.414)
+        } else {
+            y
+        }
+    };
+
+    let txt = screen_y(text);
+    let bg = screen_y(background);
+    if (bg - txt).abs() < 0.0005 {
+        return 0.0;
+    }
+
+    let lc = if bg > txt {
+        let sapc = (bg.powf(0.56) - txt.powf(0.57)) * 1.14;
+        if sapc < 0.1 {
+            0.0
+        } else {
+            sapc - 0.027
+        }
+    } else {
+        let sapc = (bg.powf(0.65) - txt.powf(0.62)) * 1.14;
+        if sapc > -0.1 {
+            0.0
+        } else {
+            sapc + 0.027
+        }
+    };
+    lc * 100.0
+}
+
 pub fn get_default_ext_color(name: &str) -> Color32 {
     let ext = name.rsplit('.').next().unwrap_or("").to_lowercase();
     let hash = ext.bytes().fold(0u32, |acc, b| acc.wrapping_add(b as u32));
     DEFAULT_EXT_COLORS[(hash as usize) % DEFAULT_EXT_COLORS.len()]
 }
 
+/// The same color at full opacity.
+pub fn opaque(color: Color32) -> Color32 {
+    let [r, g, b, _] = color.to_srgba_unmultiplied();
+    Color32::from_rgb(r, g, b)
+}
+
 pub fn parse_hex_color(hex: &str) -> Option<Color32> {
     let hex = hex.trim_start_matches('#');
     match hex.len() {
@@ -77,12 +153,8 @@ pub fn parse_hex_color(hex: &str) -> Option<Color32> {
 pub fn detect_colors_in_content(content: &str) -> Vec<DetectedColor> {
     let mut colors = Vec::new();
 
-    let hex_regex = Regex::new(r"#([0-9a-fA-F]{3}|[0-9a-fA-F]{6}|[0-9a-fA-F]{8})\b").unwrap();
-    let rgb_regex =
-        Regex::new(r"rgba?\s*\(\s*(\d+)\s*,\s*(\d+)\s*,\s*(\d+)\s*(?:,\s*([\d.]+))?\s*\)").unwrap();
-
     for (line_idx, line) in content.lines().enumerate() {
-        for mat in hex_regex.find_iter(line) {
+        for mat in HEX_REGEX.find_iter(line) {
             if let Some(color) = parse_hex_color(mat.as_str()) {
                 colors.push(DetectedColor {
                     id: format!("{}_{}", line_idx, mat.start()),
@@ -95,8 +167,8 @@ pub fn detect_colors_in_content(content: &str) -> Vec<DetectedColor> {
             }
         }
 
-        for mat in rgb_regex.find_iter(line) {
-            let caps = rgb_regex.captures(mat.as_str()).unwrap();
+        for mat in RGB_REGEX.find_iter(line) {
+            let caps = RGB_REGEX.captures(mat.as_str()).unwrap();
             let r: u8 = caps.get(1).unwrap().as_str().parse().unwrap_or(0);
             let g: u8 = caps.get(2).unwrap().as_str().parse().unwrap_or(0);
             let b: u8 = caps.get(3).unwrap().as_str().parse().unwrap_or(0);
@@ -115,7 +187,134 @@ pub fn detect_colors_in_content(content: &str) -> Vec<DetectedColor> {
                 hex_text: mat.as_str().to_string(),
             });
         }
+
+        for caps in HYPR_REGEX.captures_iter(line) {
+            let mat = caps.get(0).unwrap();
+            if let Some(color) = parse_hex_color(caps.get(1).unwrap().as_str()) {
+                colors.push(DetectedColor {
+                    id: format!("{}_{}", line_idx, mat.start()),
+                    value: color,
+                    line: line_idx,
+                    start_col: mat.start(),
+                    end_col: mat.end(),
+                    hex_text: mat.as_str().to_string(),
+                });
+            }
+        }
     }
 
+    colors.sort_by_key(|c| (c.line, c.start_col));
     colors
 }
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColorFormat {
+    Hex3,
+    Hex6,
+    Hex8,
+    Rgb,
+    Rgba,
+    /// Hyprland `rgb(rrggbb)`
+    HyprRgb,
+    /// Hyprland `rgba(rrggbbaa)`
+    HyprRgba,
+}
+
+pub fn detect_color_format(text: &str) -> ColorFormat {
+    let text = text.trim();
+    if text.starts_with("rgba(") && !text.contains(',') {
+        ColorFormat::HyprRgba
+    } else if text.starts_with("rgb(") && !text.contains(',') {
+        ColorFormat::HyprRgb
+    } else if text.starts_with("rgba") {
+        ColorFormat::Rgba
+    } else if text.starts_with("rgb") {
+        ColorFormat::Rgb
+    } else if text.starts_with('#') {
+        match text.len() - 1 {
+            3 => ColorFormat::Hex3,
+            8 => ColorFormat::Hex8,
+            _ => ColorFormat::Hex6,
+        }
+    } else {
+        ColorFormat::Hex6
+    }
+}
+
+pub fn color_to_format(color: Color32, format: &ColorFormat) -> String {
+    // Color32 is premultiplied, config files are not
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    match format {
+        ColorFormat::Hex3 => {
+            format!("#{:x}{:x}{:x}", r / 17, g / 17, b / 17)
+        }
+        ColorFormat::Hex6 => {
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        }
+        ColorFormat::Hex8 => {
+            format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+        }
+        ColorFormat::Rgb => {
+            format!("rgb({}, {}, {})", r, g, b)
+        }
+        ColorFormat::Rgba => {
+            format!("rgba({}, {}, {}, {})", r, g, b, a as f32 / 255.0)
+        }
+        ColorFormat::HyprRgb => {
+            format!("rgb({:02x}{:02x}{:02x})", r, g, b)
+        }
+        ColorFormat::HyprRgba => {
+            format!("rgba({:02x}{:02x}{:02x}{:02x})", r, g, b, a)
+        }
+    }
+}
+
+/// Formats `color` like `original` was written, including upper-case hex.
+pub fn format_like(color: Color32, original: &str) -> String {
+    let formatted = color_to_format(color, &detect_color_format(original));
+    let hex_digits = original
+        .trim_start_matches("rgba(")
+        .trim_start_matches("rgb(");
+    if hex_digits.chars().any(|c| c.is_ascii_uppercase()) {
+        formatted
+            .char_indices()
+            .map(|(i, c)| {
+                if formatted[..i].contains('#') || formatted[..i].contains('(') {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                }
+            })
+            .collect()
+    } else {
+        formatted
+    }
+}
+
+/// Rewrites every detected color in `content`. `f` returns the replacement
+/// text, or `None` to keep a color as it is. Line endings are preserved.
+pub fn map_colors_in_content(
+    content: &str,
+    mut f: impl FnMut(&DetectedColor) -> Option<String>,
+) -> String {
+    let colors = detect_colors_in_content(content);
+    let mut colors = colors.iter().peekable();
+    let mut result = String::with_capacity(content.len());
+
+    for (line_idx, line) in content.split_inclusive('\n').enumerate() {
+        let mut last_end = 0;
+        while let Some(color) = colors.next_if(|c| c.line == line_idx) {
+            if color.start_col < last_end {
+                continue;
+            }
+            if let Some(replacement) = f(color) {
+                result.push_str(&line[last_end..color.start_col]);
+                result.push_str(&replacement);
+                last_end = color.end_col;
+            }
+        }
+        result.push_str(&line[last_end..]);
+    }
+
+    result
+}
@@ -0,0 +1,189 @@
+use egui::Color32;
+
+use crate::utils::color::srgb_to_linear;
+
+/// Linear light (0-1) back to an sRGB channel.
+pub fn linear_to_srgb(value: f32) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    let c = if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
+}
+
+pub fn to_linear(color: Color32) -> [f32; 3] {
+    let [r, g, b, _] = color.to_srgba_unmultiplied();
+    [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b)]
+}
+
+/// Builds a color from linear RGB, keeping the alpha of `alpha_from`.
+pub fn from_linear(rgb: [f32; 3], alpha_from: Color32) -> Color32 {
+    Color32::from_rgba_unmultiplied(
+        linear_to_srgb(rgb[0]),
+        linear_to_srgb(rgb[1]),
+        linear_to_srgb(rgb[2]),
+        alpha_from.a(),
+    )
+}
+
+/// CIE L*a*b* (D65).
+pub fn to_lab(color: Color32) -> [f32; 3] {
+    let [r, g, b] = to_linear(color);
+    let x = (0.4124564 * r + 0.3575761 * g + 0.1804375 * b) / 0.95047;
+    let y = 0.2126729 * r + 0.7151522 * g + 0.0721750 * b;
+    let z = (0.0193339 * r + 0.119192 * g + 0.9503041 * b) / 1.08883;
+
+    let f = |t: f32| {
+        if t > 216.0 / 24389.0 {
+            t.cbrt()
+        } else {
+            (24389.0 / 27.0 * t + 16.0) / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+/// CIEDE2000 color difference. Around 2 is barely noticeable, above 10 the
+/// colors read as clearly different.
+pub fn delta_e(a: Color32, b: Color32) -> f32 {
+    let [l1, a1, b1] = to_lab(a).map(|v| v as f64);
+    let [l2, a2, b2] = to_lab(b).map(|v| v as f64);
+
+    let c1 = (a1 * a1 + b1 * b1).sqrt();
+    let c2 = (a2 * a2 + b2 * b2).sqrt();
+    let c_bar = (c1 + c2) / 2.0;
+    let g = 0.5 * (1.0 - (c_bar.powi(7) / (c_bar.powi(7) + 25f64.powi(7))).sqrt());
+    let a1p = a1 * (1.0 + g);
+    let a2p = a2 * (1.0 + g);
+    let c1p = (a1p * a1p + b1 * b1).sqrt();
+    let c2p = (a2p * a2p + b2 * b2).sqrt();
+
+    let hue = |b: f64, a: f64| {
+        if a == 0.0 && b == 0.0 {
+            0.0
+        } else {
+            let h = b.atan2(a).to_degrees();
+            if h < 0.0 {
+                h + 360.0
+            } else {
+                h
+            }
+        }
+    };
+    let h1p = hue(b1, a1p);
+    let h2p = hue(b2, a2p);
+
+    let dl = l2 - l1;
+    let dc = c2p - c1p;
+    let dh_angle = if c1p * c2p == 0.0 {
+        0.0
+    } else if (h2p - h1p).abs() <= 180.0 {
+        h2p - h1p
+    } else if h2p - h1p > 180.0 {
+        h2p - h1p - 360.0
+    } else {
+        h2p - h1p + 360.0
+    };
+    let dh = 2.0 * (c1p * c2p).sqrt() * (dh_angle.to_radians() / 2.0).sin();
+
+    let l_bar = (l1 + l2) / 2.0;
+    let c_bar_p = (c1p + c2p) / 2.0;
+    let h_bar_p = if c1p * c2p == 0.0 {
+        h1p + h2p
+    } else if (h1p - h2p).abs() <= 180.0 {
+        (h1p + h2p) / 2.0
+    } else if h1p + h2p < 360.0 {
+        (h1p + h2p + 360.0) / 2.0
+    } else {
+        (h1p + h2p - 360.0) / 2.0
+    };
+
+    let t = 1.0 - 0.17 * (h_bar_p - 30.0).to_radians().cos()
+        + 0.24 * (2.0 * h_bar_p).to_radians().cos()
+        + 0.32 * (3.0 * h_bar_p + 6.0).to_radians().cos()
+        - 0.20 * (4.0 * h_bar_p - 63.0).to_radians().cos();
+    let sl = 1.0 + 0.015 * (l_bar - 50.0).powi(2) / (20.0 + (l_bar - 50.0).powi(2)).sqrt();
+    let sc = 1.0 + 0.045 * c_bar_p;
+    let sh = 1.0 + 0.015 * c_bar_p * t;
+    let d_theta = 30.0 * (-((h_bar_p - 275.0) / 25.0).powi(2)).exp();
+    let rc = 2.0 * (c_bar_p.powi(7) / (c_bar_p.powi(7) + 25f64.powi(7))).sqrt();
+    let rt = -rc * (2.0 * d_theta).to_radians().sin();
+
+    let (tl, tc, th) = (dl / sl, dc / sc, dh / sh);
+    (tl * tl + tc * tc + th * th + rt * tc * th).sqrt() as f32
+}
+
+/// OKLab `[L, a, b]`, L from 0 to 1.
+pub fn to_oklab(color: Color32) -> [f32; 3] {
+    let [r, g, b] = to_linear(color);
+    let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+    let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+    let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+    [
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    ]
+}
+
+fn oklab_to_linear([l, a, b]: [f32; 3]) -> [f32; 3] {
+    let l_ = (l + 0.396_337_78 * a + 0.215_803_76 * b).powi(3);
+    let m_ = (l - 0.105_561_346 * a - 0.063_854_17 * b).powi(3);
+    let s_ = (l - 0.089_484_18 * a - 1.291_485_5 * b).powi(3);
+    [
+        4.076_741_7 * l_ - 3.307_711_6 * m_ + 0.230_969_94 * s_,
+        -1.268_438 * l_ + 2.609_757_4 * m_ - 0.341_319_38 * s_,
+        -0.0041960863 * l_ - 0.703_418_6 * m_ + 1.707_614_7 * s_,
+    ]
+}
+
+/// Converts OKLab back to sRGB. Out of gamut colors keep their lightness and
+/// hue and lose chroma until they fit.
+pub fn from_oklab(lab: [f32; 3], alpha_from: Color32) -> Color32 {
+    let in_gamut = |rgb: [f32; 3]| rgb.iter().all(|v| (-0.0001..=1.0001).contains(v));
+    let mut rgb = oklab_to_linear(lab);
+    if !in_gamut(rgb) {
+        let (mut lo, mut hi) = (0.0f32, 1.0f32);
+        for _ in 0..16 {
+            let mid = (lo + hi) / 2.0;
+            if in_gamut(oklab_to_linear([lab[0], lab[1] * mid, lab[2] * mid])) {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        rgb = oklab_to_linear([lab[0], lab[1] * lo, lab[2] * lo]);
+    }
+    from_linear(rgb, alpha_from)
+}
+
+/// OKLCH `[L, C, h]`, hue in degrees.
+pub fn to_oklch(color: Color32) -> [f32; 3] {
+    let [l, a, b] = to_oklab(color);
+    let h = b.atan2(a).to_degrees();
+    [
+        l,
+        (a * a + b * b).sqrt(),
+        if h < 0.0 { h + 360.0 } else { h },
+    ]
+}
+
+pub fn from_oklch([l, c, h]: [f32; 3], alpha_from: Color32) -> Color32 {
+    let rad = h.to_radians();
+    from_oklab(
+        [l.clamp(0.0, 1.0), c * rad.cos(), c * rad.sin()],
+        alpha_from,
+    )
+}
+
+/// Blends `a` towards `b` by `t` (0-1) in OKLab.
+pub fn mix(a: Color32, b: Color32, t: f32) -> Color32 {
+    let (la, lb) = (to_oklab(a), to_oklab(b));
+    from_oklab(
+        [0, 1, 2].map(|i| la[i] + (lb[i] - la[i]) * t),
+        Color32::from_rgb(0, 0, 0),
+    )
+}
@@ -0,0 +1,99 @@
+use egui::Color32;
+
+use crate::utils::colorspace::{delta_e, from_linear, to_linear};
+
+/// Color vision deficiency simulated when rendering swatches and previews.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CvdMode {
+    #[default]
+    Normal,
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+    Achromatopsia,
+}
+
+impl CvdMode {
+    pub const ALL: [CvdMode; 5] = [
+        CvdMode::Normal,
+        CvdMode::Protanopia,
+        CvdMode::Deuteranopia,
+        CvdMode::Tritanopia,
+        CvdMode::Achromatopsia,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CvdMode::Normal => "Normal vision",
+            CvdMode::Protanopia => "Protanopia",
+            CvdMode::Deuteranopia => "Deuteranopia",
+            CvdMode::Tritanopia => "Tritanopia",
+            CvdMode::Achromatopsia => "Achromatopsia",
+        }
+    }
+
+    /// Machado et al. (2009) matrices at full severity, in linear RGB.
+    fn matrix(&self) -> Option<[[f32; 3]; 3]> {
+        match self {
+            CvdMode::Normal => None,
+            CvdMode::Protanopia => Some([
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ]),
+            CvdMode::Deuteranopia => Some([
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ]),
+            CvdMode::Tritanopia => Some([
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ]),
+            CvdMode::Achromatopsia => Some([
+                [0.2126, 0.7152, 0.0722],
+                [0.2126, 0.7152, 0.0722],
+                [0.2126, 0.7152, 0.0722],
+            ]),
+        }
+    }
+}
+
+/// How `color` appears to someone with the given deficiency.
+pub fn simulate(color: Color32, mode: CvdMode) -> Color32 {
+    let Some(m) = mode.matrix() else {
+        return color;
+    };
+    let rgb = to_linear(color);
+    let mapped = [0, 1, 2].map(|row| m[row][0] * rgb[0] + m[row][1] * rgb[1] + m[row][2] * rgb[2]);
+    from_linear(mapped, color)
+}
+
+/// Pairs of colors that are clearly different with normal vision but close to
+/// identical under `mode`. Returns `(i, j, simulated delta E)` sorted from the
+/// most confusable pair.
+pub fn confusable_pairs(colors: &[Color32], mode: CvdMode) -> Vec<(usize, usize, f32)> {
+    const DISTINCT: f32 = 15.0;
+    const CONFUSED: f32 = 6.0;
+
+    if mode == CvdMode::Normal {
+        return vec![];
+    }
+
+    let simulated: Vec<Color32> = colors.iter().map(|c| simulate(*c, mode)).collect();
+    let mut pairs = Vec::new();
+    for i in 0..colors.len() {
+        for j in (i + 1)..colors.len() {
+            if delta_e(colors[i], colors[j]) < DISTINCT {
+                continue;
+            }
+            let sim = delta_e(simulated[i], simulated[j]);
+            if sim < CONFUSED {
+                pairs.push((i, j, sim));
+            }
+        }
+    }
+    pairs.sort_by(|a, b| a.2.total_cmp(&b.2));
+    pairs
+}
@@ -1,2 +1,4 @@
 pub mod color;
+pub mod colorspace;
+pub mod cvd;
 pub mod path;